@@ -0,0 +1,42 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use level3bug::{checksum_input_string, compute_checksum, from_file};
+
+fn checksum_benchmark(c: &mut Criterion) {
+    // ~3000 orders captured from the live feed
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+    let crc_str = checksum_input_string(level3_data);
+
+    c.bench_function("compute_checksum", |b| {
+        b.iter(|| compute_checksum(black_box(level3_data)))
+    });
+    c.bench_function("checksum_input_string", |b| {
+        b.iter(|| checksum_input_string(black_box(level3_data)))
+    });
+    c.bench_function("crc32 hash", |b| {
+        b.iter(|| crc32fast::hash(black_box(crc_str.as_bytes())))
+    });
+}
+
+criterion_group!(benches, checksum_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use level3bug::{parse_snapshot, Level3Data};
+
+fn parse_benchmark(c: &mut Criterion) {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+
+    c.bench_function("parse_snapshot", |b| {
+        b.iter(|| parse_snapshot(black_box(&line_str)).unwrap())
+    });
+    // the previous approach: parse into a Value and deserialize a clone of the data array
+    c.bench_function("parse via cloned Value", |b| {
+        b.iter(|| {
+            let snapshot: serde_json::Value = serde_json::from_str(black_box(&line_str)).unwrap();
+            let level3_data: Vec<Level3Data> =
+                serde_json::from_value(snapshot["data"].clone()).unwrap();
+            level3_data
+        })
+    });
+}
+
+criterion_group!(benches, parse_benchmark);
+criterion_main!(benches);
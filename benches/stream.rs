@@ -0,0 +1,111 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use level3bug::{
+    from_file, parse_snapshot, stream_checksums, to_json, try_compute_checksum, ChecksumOptions,
+};
+
+// counts the live heap bytes to report the peak memory of each approach
+struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn peak_bytes<T>(f: impl FnOnce() -> T) -> usize {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    black_box(f());
+    PEAK.load(Ordering::Relaxed) - baseline
+}
+
+// the first ask of the bug capture repeated, 100k orders per side with four orders per level
+fn large_snapshot() -> String {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    let template = level3_data.asks[0].clone();
+    level3_data.asks = (0..100_000)
+        .map(|index| level3bug::Order {
+            order_id: format!("O{}", index),
+            limit_price: template.limit_price + (index / 4) as f64,
+            ..template.clone()
+        })
+        .collect();
+    level3_data.bids = (0..100_000)
+        .map(|index| level3bug::Order {
+            order_id: format!("B{}", index),
+            limit_price: 60_000.0 - (index / 4) as f64,
+            ..template.clone()
+        })
+        .collect();
+    format!(
+        r#"{{"channel":"level3","type":"snapshot","data":[{}]}}"#,
+        to_json(&level3_data).unwrap()
+    )
+}
+
+fn stream_benchmark(c: &mut Criterion) {
+    let line_str = large_snapshot();
+    let opts = ChecksumOptions::default();
+
+    let full = peak_bytes(|| {
+        let level3_data = parse_snapshot(&line_str).unwrap();
+        try_compute_checksum(&level3_data[0], &opts).unwrap()
+    });
+    let streamed = peak_bytes(|| stream_checksums(line_str.as_bytes(), &opts).unwrap());
+    println!(
+        "peak heap for {} bytes of JSON: full parse {} bytes, streamed {} bytes",
+        line_str.len(),
+        full,
+        streamed
+    );
+
+    c.bench_function("full parse checksum", |b| {
+        b.iter(|| {
+            let level3_data = parse_snapshot(black_box(&line_str)).unwrap();
+            try_compute_checksum(&level3_data[0], &opts).unwrap()
+        })
+    });
+    c.bench_function("streamed checksum", |b| {
+        b.iter(|| stream_checksums(black_box(line_str.as_bytes()), &opts).unwrap())
+    });
+}
+
+criterion_group!(benches, stream_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Feeds arbitrary bytes to the snapshot parser: parsing, extracting `data` and checksumming
+// whatever parses must return errors, never panic. Run with `cargo +nightly fuzz run parse`,
+// the snapshots in the crate root make a good seed corpus.
+
+#![no_main]
+
+use level3bug::{extract_data, parse_snapshot, try_compute_checksum, ChecksumOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(line_str) = std::str::from_utf8(data) {
+        if let Ok(level3_data) = parse_snapshot(line_str) {
+            for level3_data in &level3_data {
+                let opts = ChecksumOptions::for_symbol(&level3_data.symbol);
+                let _ = try_compute_checksum(level3_data, &opts);
+            }
+        }
+    }
+    if let Ok(value) = serde_json::from_slice(data) {
+        let _ = extract_data(value);
+    }
+});
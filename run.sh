@@ -1,9 +1,9 @@
 #!/bin/bash
 
 # to parse buggy json run: cargo run
-# to parse api docs reference json run: cargo run -- ref
+# to parse api docs reference json run: cargo run -- --reference
 
-cargo run
+cargo run -- --file level3-bug.json
 
 echo
 echo
@@ -11,4 +11,4 @@ echo "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx
 echo
 echo
 
-cargo run -- ref
+cargo run -- --reference --file level3-doc.json
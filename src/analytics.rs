@@ -0,0 +1,157 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::checksum::ChecksumOptions;
+use crate::level2::to_level2;
+use crate::level3::{Level3Data, Order, Side};
+use crate::symbol::precision_for;
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct BookStats {
+    pub bid_volume: f64,
+    pub ask_volume: f64,
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub bid_orders: usize,
+    pub ask_orders: usize,
+}
+
+/// Volume, price level and order counts per side, the volumes are summed at the checksum
+/// precision like `to_level2`.
+pub fn stats(data: &Level3Data) -> BookStats {
+    let bids = to_level2(data, Side::Bid);
+    let asks = to_level2(data, Side::Ask);
+    BookStats {
+        bid_volume: total_qty(&bids),
+        ask_volume: total_qty(&asks),
+        bid_levels: bids.len(),
+        ask_levels: asks.len(),
+        bid_orders: data.bids.len(),
+        ask_orders: data.asks.len(),
+    }
+}
+
+/// Order book imbalance `(bid_vol - ask_vol) / (bid_vol + ask_vol)` over the top price levels,
+/// ranges from -1 (only asks) to 1 (only bids). An empty book has an imbalance of 0.0.
+pub fn imbalance(data: &Level3Data, levels: usize) -> f64 {
+    let bid_vol = volume(data, Side::Bid, levels);
+    let ask_vol = volume(data, Side::Ask, levels);
+    let total_vol = bid_vol + ask_vol;
+    if total_vol == 0.0 {
+        return 0.0;
+    }
+    (bid_vol - ask_vol) / total_vol
+}
+
+/// Value of the resting liquidity, `limit_price * order_qty` summed over the top `levels` price
+/// levels of one side. The level quantities come from `to_level2`.
+pub fn notional(data: &Level3Data, side: Side, levels: usize) -> f64 {
+    to_level2(data, side)
+        .iter()
+        .take(levels)
+        .map(|(price, qty)| price * qty)
+        .sum()
+}
+
+/// Volume weighted average price to fill `size` by walking the orders of one side from the best
+/// price outward. Returns `None` if the side doesn't hold enough quantity (or `size` isn't
+/// positive).
+pub fn vwap_for_size(data: &Level3Data, side: Side, size: f64) -> Option<f64> {
+    if size <= 0.0 {
+        return None;
+    }
+    let mut remaining = size;
+    let mut notional = 0.0;
+    for order in data.orders(side) {
+        let fill_qty = order.order_qty.min(remaining);
+        notional += fill_qty * order.limit_price;
+        remaining -= fill_qty;
+        if remaining <= 0.0 {
+            return Some(notional / size);
+        }
+    }
+    None
+}
+
+/// Difference between the best ask and the best bid, `None` if either side is empty.
+pub fn spread(data: &Level3Data) -> Option<f64> {
+    Some(data.best_ask()?.limit_price - data.best_bid()?.limit_price)
+}
+
+/// Average of the best ask and the best bid, `None` if either side is empty.
+pub fn mid_price(data: &Level3Data) -> Option<f64> {
+    Some((data.best_ask()?.limit_price + data.best_bid()?.limit_price) / 2.0)
+}
+
+/// All orders of one side resting at `price`, oldest first.
+///
+/// Prices are compared at the symbol's price precision (see `precision_for`) rather than with
+/// exact float equality.
+pub fn orders_at_price(data: &Level3Data, side: Side, price: f64) -> Vec<&Order> {
+    let price_factor = precision_for(&data.symbol)
+        .unwrap_or_default()
+        .price_factor();
+    let price_i = (price * price_factor).round() as i64;
+    let mut orders: Vec<&Order> = data
+        .orders(side)
+        .iter()
+        .filter(|order| (order.limit_price * price_factor).round() as i64 == price_i)
+        .collect();
+    orders.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.order_id.cmp(&b.order_id))
+    });
+    orders
+}
+
+/// The first `n` orders of one side in book order, best price first, each order counts on its
+/// own rather than by price level. All orders of the side if it has fewer than `n`.
+pub fn top_orders(data: &Level3Data, side: Side, n: usize) -> &[Order] {
+    let orders = data.orders(side);
+    &orders[..n.min(orders.len())]
+}
+
+/// Side and zero based price level index of the order, `None` if the order id isn't in the book.
+///
+/// The order contributes to the checksum if the level index is below the checksum depth (see
+/// `ChecksumOptions::includes_level`, which counts levels from 1).
+pub fn order_rank(data: &Level3Data, order_id: &str) -> Option<(Side, usize)> {
+    let opts = ChecksumOptions::for_symbol(&data.symbol);
+    [Side::Bid, Side::Ask].into_iter().find_map(|side| {
+        let orders = data.orders(side);
+        let index = orders.iter().position(|order| order.order_id == order_id)?;
+        // price changes before the order, grouped like the checksum groups the levels
+        let level = orders[..=index]
+            .windows(2)
+            .filter(|pair| !opts.same_price(pair[0].limit_price, pair[1].limit_price))
+            .count();
+        Some((side, level))
+    })
+}
+
+fn volume(data: &Level3Data, side: Side, levels: usize) -> f64 {
+    let level2 = to_level2(data, side);
+    total_qty(&level2[..levels.min(level2.len())])
+}
+
+fn total_qty(levels: &[(f64, f64)]) -> f64 {
+    levels.iter().map(|(_, qty)| qty).sum()
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compact binary encoding of `Level3Data` with bincode, enabled with the `bincode` feature.
+//!
+//! The JSON serde impls can't be reused, bincode isn't self-describing and doesn't support the
+//! number-or-string fields or the skipped `event`. The data is mapped onto plain structs with
+//! the timestamps as unix nanoseconds instead.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::error::Level3Error;
+use crate::level3::{Level3Data, Order, OrderEvent};
+
+#[derive(Serialize, Deserialize)]
+struct BinaryLevel3Data {
+    symbol: String,
+    bids: Vec<BinaryOrder>,
+    asks: Vec<BinaryOrder>,
+    checksum: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryOrder {
+    event: Option<u8>,
+    order_id: String,
+    limit_price: f64,
+    order_qty: f64,
+    timestamp: i128,
+}
+
+impl From<&Order> for BinaryOrder {
+    fn from(order: &Order) -> Self {
+        BinaryOrder {
+            // the integer codes the JSON deserializer accepts as well, 3 for the snapshot marker
+            // only exists in the binary format
+            event: order.event.as_ref().map(|event| match event {
+                OrderEvent::Add => 0,
+                OrderEvent::Modify => 1,
+                OrderEvent::Delete => 2,
+                OrderEvent::Snapshot => 3,
+            }),
+            order_id: order.order_id.clone(),
+            limit_price: order.limit_price,
+            order_qty: order.order_qty,
+            timestamp: order.timestamp.unix_timestamp_nanos(),
+        }
+    }
+}
+
+impl TryFrom<BinaryOrder> for Order {
+    type Error = Level3Error;
+
+    fn try_from(order: BinaryOrder) -> Result<Self, Level3Error> {
+        let invalid = |msg: String| Level3Error::Bincode(Box::new(bincode::ErrorKind::Custom(msg)));
+        let event = match order.event {
+            None => None,
+            Some(0) => Some(OrderEvent::Add),
+            Some(1) => Some(OrderEvent::Modify),
+            Some(2) => Some(OrderEvent::Delete),
+            Some(3) => Some(OrderEvent::Snapshot),
+            Some(code) => return Err(invalid(format!("unknown order event code {}", code))),
+        };
+        let timestamp = OffsetDateTime::from_unix_timestamp_nanos(order.timestamp)
+            .map_err(|err| invalid(err.to_string()))?;
+        Ok(Order {
+            event,
+            order_id: order.order_id,
+            limit_price: order.limit_price,
+            order_qty: order.order_qty,
+            timestamp,
+        })
+    }
+}
+
+/// Encodes the data with bincode, timestamps are stored as UTC.
+pub fn to_bytes(data: &Level3Data) -> Result<Vec<u8>, Level3Error> {
+    let binary = BinaryLevel3Data {
+        symbol: data.symbol.clone(),
+        bids: data.bids.iter().map(BinaryOrder::from).collect(),
+        asks: data.asks.iter().map(BinaryOrder::from).collect(),
+        checksum: data.checksum,
+    };
+    Ok(bincode::serialize(&binary)?)
+}
+
+/// Decodes data written by `to_bytes`.
+pub fn from_bytes(bytes: &[u8]) -> Result<Level3Data, Level3Error> {
+    let binary: BinaryLevel3Data = bincode::deserialize(bytes)?;
+    let orders = |orders: Vec<BinaryOrder>| -> Result<Vec<Order>, Level3Error> {
+        orders.into_iter().map(Order::try_from).collect()
+    };
+    Ok(Level3Data {
+        symbol: binary.symbol,
+        bids: orders(binary.bids)?,
+        asks: orders(binary.asks)?,
+        checksum: binary.checksum,
+    })
+}
@@ -0,0 +1,380 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::checksum::{checksum_of, side_levels, ChecksumOptions};
+use crate::error::Level3Error;
+use crate::level3::{best_of, sort_orders, Level3Data, Order, OrderEvent, Side};
+use crate::message::Message;
+
+/// Live level3 book that is kept sorted while add/modify/delete events are applied.
+///
+/// Bids are sorted by descending price, asks by ascending price, orders at the same price keep
+/// their arrival order. An index by order id is kept in sync with the sorted views.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    symbol: String,
+    bids: Vec<Order>,
+    asks: Vec<Order>,
+    index: HashMap<String, (Side, Order)>,
+    checksum_opts: ChecksumOptions,
+    checksum_cache: RefCell<ChecksumCache>,
+}
+
+// books are equal by their orders and options, the index follows the orders and the cache is
+// only filled once the checksum was asked for
+impl PartialEq for OrderBook {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.bids == other.bids
+            && self.asks == other.asks
+            && self.checksum_opts == other.checksum_opts
+    }
+}
+
+// digits of the contributing levels per side, None once a change touched those levels
+#[derive(Debug, Clone, Default)]
+struct ChecksumCache {
+    bids: Option<String>,
+    asks: Option<String>,
+}
+
+impl ChecksumCache {
+    fn side_mut(&mut self, side: Side) -> &mut Option<String> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+}
+
+impl OrderBook {
+    pub fn new(symbol: impl Into<String>) -> Self {
+        let symbol = symbol.into();
+        OrderBook {
+            checksum_opts: ChecksumOptions::for_symbol(&symbol),
+            symbol,
+            bids: Vec::new(),
+            asks: Vec::new(),
+            index: HashMap::new(),
+            checksum_cache: RefCell::default(),
+        }
+    }
+
+    /// Builds a book from a snapshot, the orders are re-sorted with `cmp_orders` in case the
+    /// snapshot isn't in checksum order.
+    ///
+    /// Every order is inserted whatever its event, the `Snapshot` marker some variants send is
+    /// dropped like the event of an added order. An order id that appears more than once keeps
+    /// its first occurrence (asks before bids) like `merge` does, `check_unique_ids` reports such
+    /// a snapshot.
+    pub fn from_snapshot(data: &Level3Data) -> Self {
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        let mut index = HashMap::new();
+        for (side, order) in data {
+            if index.contains_key(&order.order_id) {
+                continue;
+            }
+            let order = Order {
+                event: None,
+                ..order.clone()
+            };
+            index.insert(order.order_id.clone(), (side, order.clone()));
+            match side {
+                Side::Bid => bids.push(order),
+                Side::Ask => asks.push(order),
+            }
+        }
+        sort_orders(Side::Bid, &mut bids);
+        sort_orders(Side::Ask, &mut asks);
+        OrderBook {
+            symbol: data.symbol.clone(),
+            bids,
+            asks,
+            index,
+            checksum_opts: ChecksumOptions::for_symbol(&data.symbol),
+            checksum_cache: RefCell::default(),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn bids(&self) -> &[Order] {
+        &self.bids
+    }
+
+    pub fn asks(&self) -> &[Order] {
+        &self.asks
+    }
+
+    pub fn best_bid(&self) -> Option<&Order> {
+        best_of(&self.bids)
+    }
+
+    pub fn best_ask(&self) -> Option<&Order> {
+        best_of(&self.asks)
+    }
+
+    pub fn get_order(&self, order_id: &str) -> Option<&Order> {
+        self.index.get(order_id).map(|(_, order)| order)
+    }
+
+    /// Copy of the current book as snapshot data, with the checksum computed by `try_checksum`.
+    pub fn to_snapshot(&self) -> Result<Level3Data, Level3Error> {
+        Ok(Level3Data {
+            symbol: self.symbol.clone(),
+            bids: self.bids.clone(),
+            asks: self.asks.clone(),
+            checksum: self.try_checksum()?,
+        })
+    }
+
+    pub fn set_checksum_options(&mut self, opts: ChecksumOptions) {
+        self.checksum_opts = opts;
+        *self.checksum_cache.get_mut() = ChecksumCache::default();
+    }
+
+    /// Checksum of the current book, see `try_checksum`.
+    ///
+    /// Panics if a price or qty does not fit the checksum precision.
+    pub fn checksum(&self) -> u32 {
+        self.try_checksum().unwrap()
+    }
+
+    /// Checksum of the current book using the book's `ChecksumOptions`.
+    ///
+    /// The digit string of each side is cached and only rebuilt after an event touched one of
+    /// the price levels that contribute to the checksum (or the side has fewer levels than the
+    /// checksum depth). Events on deeper levels leave the cache alone, the CRC32 itself is
+    /// always rehashed over the cached digits.
+    pub fn try_checksum(&self) -> Result<u32, Level3Error> {
+        let mut cache = self.checksum_cache.borrow_mut();
+        let mut crc_str = String::new();
+        for side in [Side::Ask, Side::Bid] {
+            let digits = cache.side_mut(side);
+            if digits.is_none() {
+                let levels = side_levels(self.side(side), side, &self.checksum_opts)?;
+                *digits = Some(levels.concat());
+            }
+            crc_str.push_str(digits.as_ref().unwrap());
+        }
+        Ok(crc32fast::hash(crc_str.as_bytes()))
+    }
+
+    /// Applies a single order event to the given side of the book.
+    ///
+    /// Orders without an event or with the `Snapshot` marker are inserted like `Add`. A `Modify`
+    /// that changes the price moves the order to the back of its new price level, a qty change
+    /// keeps its place. Adding an order id that is already in the book, or modifying or deleting
+    /// one that is not, is an error.
+    pub fn apply(&mut self, side: Side, order: &Order) -> Result<(), Level3Error> {
+        match order.event {
+            None | Some(OrderEvent::Add) | Some(OrderEvent::Snapshot) => {
+                if self.index.contains_key(&order.order_id) {
+                    return Err(Level3Error::DuplicateOrder {
+                        order_id: order.order_id.clone(),
+                    });
+                }
+                self.invalidate_checksum(side, order.limit_price);
+                self.insert(side, order);
+                Ok(())
+            }
+            Some(OrderEvent::Modify) => {
+                let index = self.position(side, &order.order_id)?;
+                self.invalidate_checksum(side, self.side(side)[index].limit_price);
+                self.invalidate_checksum(side, order.limit_price);
+                let resting = &mut self.side_mut(side)[index];
+                let price_changed = resting.limit_price != order.limit_price;
+                resting.limit_price = order.limit_price;
+                resting.order_qty = order.order_qty;
+                resting.timestamp = order.timestamp;
+                let resting = resting.clone();
+                if price_changed {
+                    // a new price moves the order to the back of its new price level
+                    self.side_mut(side).remove(index);
+                    self.insert(side, &resting);
+                } else {
+                    self.index.insert(order.order_id.clone(), (side, resting));
+                }
+                Ok(())
+            }
+            Some(OrderEvent::Delete) => {
+                let index = self.position(side, &order.order_id)?;
+                self.invalidate_checksum(side, self.side(side)[index].limit_price);
+                self.side_mut(side).remove(index);
+                self.index.remove(&order.order_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies all bid and ask events of an update message.
+    pub fn apply_data(&mut self, data: &Level3Data) -> Result<(), Level3Error> {
+        for bid in &data.bids {
+            self.apply(Side::Bid, bid)?;
+        }
+        for ask in &data.asks {
+            self.apply(Side::Ask, ask)?;
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, side: Side, order: &Order) {
+        let order = Order {
+            event: None,
+            ..order.clone()
+        };
+        let index = match side {
+            Side::Bid => self
+                .bids
+                .partition_point(|bid| bid.limit_price >= order.limit_price),
+            Side::Ask => self
+                .asks
+                .partition_point(|ask| ask.limit_price <= order.limit_price),
+        };
+        self.index
+            .insert(order.order_id.clone(), (side, order.clone()));
+        self.side_mut(side).insert(index, order);
+    }
+
+    // looks up the resting price in the index and only scans that price level
+    fn position(&self, side: Side, order_id: &str) -> Result<usize, Level3Error> {
+        let unknown_order = || Level3Error::UnknownOrder {
+            order_id: order_id.to_string(),
+        };
+        let (resting_side, resting) = self.index.get(order_id).ok_or_else(unknown_order)?;
+        if *resting_side != side {
+            return Err(unknown_order());
+        }
+        let price = resting.limit_price;
+        let (orders, start) = match side {
+            Side::Bid => (
+                &self.bids,
+                self.bids.partition_point(|bid| bid.limit_price > price),
+            ),
+            Side::Ask => (
+                &self.asks,
+                self.asks.partition_point(|ask| ask.limit_price < price),
+            ),
+        };
+        orders[start..]
+            .iter()
+            .position(|order| order.order_id == order_id)
+            .map(|offset| start + offset)
+            .ok_or_else(unknown_order)
+    }
+
+    // drops the cached digits if a change at this price reaches the contributing levels
+    fn invalidate_checksum(&mut self, side: Side, price: f64) {
+        let last_level = self.checksum_opts.last_level(side);
+        let mut boundary: Option<f64> = None;
+        let mut price_level_count = 0;
+        for order in self.side(side) {
+            if !boundary
+                .is_some_and(|boundary| self.checksum_opts.same_price(boundary, order.limit_price))
+            {
+                boundary = Some(order.limit_price);
+                price_level_count += 1;
+                if price_level_count == last_level {
+                    break;
+                }
+            }
+        }
+        let affected = match boundary {
+            Some(boundary) if price_level_count == last_level => {
+                self.checksum_opts.same_price(price, boundary)
+                    || match side {
+                        Side::Bid => price >= boundary,
+                        Side::Ask => price <= boundary,
+                    }
+            }
+            _ => true,
+        };
+        if affected {
+            *self.checksum_cache.get_mut().side_mut(side) = None;
+        }
+    }
+
+    fn side(&self, side: Side) -> &[Order] {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut Vec<Order> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+}
+
+/// Applies update messages in order and verifies the embedded checksum after each one.
+///
+/// Only data for the book's symbol is used, a snapshot message resets the book. A checksum
+/// mismatch reports the index of the failing message, the book needs a resync from there.
+pub fn apply_updates(book: &mut OrderBook, updates: &[Message]) -> Result<(), Level3Error> {
+    for (index, message) in updates.iter().enumerate() {
+        for data in message.data() {
+            if data.symbol != book.symbol {
+                continue;
+            }
+            match message {
+                Message::Snapshot(_) => {
+                    let checksum_opts = book.checksum_opts.clone();
+                    *book = OrderBook::from_snapshot(data);
+                    book.set_checksum_options(checksum_opts);
+                }
+                Message::Update(_) => book.apply_data(data)?,
+            }
+            let computed = book.try_checksum()?;
+            if computed != data.checksum {
+                return Err(Level3Error::UpdateChecksumMismatch {
+                    index,
+                    expected: data.checksum,
+                    computed,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checksum of the live book computed from scratch with the snapshot algorithm.
+///
+/// The sides are copied and sorted by price (stable, so orders at the same price keep their
+/// arrival order) and then hashed like `compute_checksum` with the book's `ChecksumOptions`. The
+/// result equals `compute_checksum` on a snapshot of the same book and serves as a cross check of
+/// the cached `OrderBook::checksum`.
+///
+/// Panics if a price or qty does not fit the checksum precision.
+pub fn book_checksum(book: &OrderBook) -> u32 {
+    let mut bids = book.bids.clone();
+    let mut asks = book.asks.clone();
+    bids.sort_by(|a, b| b.limit_price.total_cmp(&a.limit_price));
+    asks.sort_by(|a, b| a.limit_price.total_cmp(&b.limit_price));
+    checksum_of(&asks, &bids, &book.checksum_opts)
+}
@@ -0,0 +1,86 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use time::OffsetDateTime;
+
+use crate::level3::{Order, OrderEvent};
+
+/// Builds `Order` values for tests, enabled with the `test-util` feature.
+///
+/// The timestamp defaults to now, the event to `None` and the price and qty to zero.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    pub fn new() -> Self {
+        OrderBuilder {
+            order: Order {
+                event: None,
+                order_id: String::new(),
+                limit_price: 0.0,
+                order_qty: 0.0,
+                timestamp: OffsetDateTime::now_utc(),
+            },
+        }
+    }
+
+    pub fn id(mut self, order_id: impl Into<String>) -> Self {
+        self.order.order_id = order_id.into();
+        self
+    }
+
+    pub fn price(mut self, limit_price: f64) -> Self {
+        self.order.limit_price = limit_price;
+        self
+    }
+
+    pub fn qty(mut self, order_qty: f64) -> Self {
+        self.order.order_qty = order_qty;
+        self
+    }
+
+    pub fn event(mut self, event: OrderEvent) -> Self {
+        self.order.event = Some(event);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: OffsetDateTime) -> Self {
+        self.order.timestamp = timestamp;
+        self
+    }
+
+    pub fn build(self) -> Order {
+        self.order
+    }
+}
+
+impl Default for OrderBuilder {
+    fn default() -> Self {
+        OrderBuilder::new()
+    }
+}
+
+impl Order {
+    pub fn builder() -> OrderBuilder {
+        OrderBuilder::new()
+    }
+}
@@ -0,0 +1,951 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::cell::RefCell;
+use std::fmt::{Debug, Display};
+
+use tracing::{trace, trace_span};
+
+use crate::analytics::order_rank;
+pub use crate::core_checksum::Precision;
+use crate::error::Level3Error;
+use crate::level3::{Level3Data, Order, Side};
+use crate::symbol::precision_for;
+
+/// How a price or qty with more decimals than the checksum precision is turned into digits, see
+/// `ChecksumOptions::strict_precision`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum RoundingMode {
+    /// Rounds to the nearest value at the precision, halves away from zero like `f64::round`.
+    #[default]
+    Round,
+    /// Drops the extra decimals, towards zero.
+    Truncate,
+    /// Rounds up to the next value at the precision.
+    Ceil,
+    /// Rounds down to the previous value at the precision.
+    Floor,
+}
+
+/// Layout of the checksum input string.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum ChecksumVersion {
+    /// Kraken's v1 book checksum: one price and the total qty per price level. The v1 API has
+    /// no level3 channel, this reproduces the v1 (level2) checksum of the aggregated book.
+    V1,
+    /// Kraken's v2 level3 checksum: price and qty of every order on the contributing levels.
+    #[default]
+    V2,
+}
+
+/// Controls which price levels of the book feed into the checksum.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ChecksumOptions {
+    pub precision: Precision,
+    /// Number of price levels per side that contribute, Kraken's spec uses the top 10, the
+    /// smallest of the `SNAPSHOT_DEPTHS`.
+    pub depth: usize,
+    /// 1-based ask levels that are left out without counting towards `depth`.
+    ///
+    /// Kraken's checksum for `level3-bug.json` only matches when ask levels 10 and 11 are
+    /// skipped (see `ChecksumOptions::level3_bug`), this is not part of the spec.
+    pub skip_ask_levels: Vec<usize>,
+    /// Applied by the float scaling without `strict_precision`, the decimal checksum always
+    /// requires exact values.
+    pub rounding: RoundingMode,
+    /// A price or qty with significant digits beyond the precision is a `Level3Error::Precision`,
+    /// Kraken's spec implies they always fit. On by default, when off such values are scaled
+    /// with `rounding`.
+    pub strict_precision: bool,
+    pub version: ChecksumVersion,
+    /// Prices closer than half a unit of the price precision (0.05 at one decimal) belong to the
+    /// same price level, so two parses of `449.39` that differ in the last bit are not split
+    /// into two levels. On by default, the decimal checksum always compares exact decimals.
+    pub level_tolerance: bool,
+}
+
+impl ChecksumOptions {
+    /// Options that reproduce the checksum Kraken sent for `level3-bug.json`.
+    pub fn level3_bug() -> Self {
+        ChecksumOptions {
+            skip_ask_levels: vec![10, 11],
+            ..ChecksumOptions::default()
+        }
+    }
+
+    /// Default options with the precision registered for the symbol (see `precision_for`),
+    /// unknown symbols use the default precision.
+    pub fn for_symbol(symbol: &str) -> Self {
+        ChecksumOptions {
+            precision: precision_for(symbol).unwrap_or_default(),
+            ..ChecksumOptions::default()
+        }
+    }
+
+    /// Whether the 1-based price level of the given side contributes to the checksum.
+    pub fn includes_level(&self, side: Side, level: usize) -> bool {
+        if side == Side::Ask && self.skip_ask_levels.contains(&level) {
+            return false;
+        }
+        level >= 1 && level <= self.last_level(side)
+    }
+
+    /// Whether two prices belong to the same price level, see `level_tolerance`.
+    pub fn same_price(&self, a: f64, b: f64) -> bool {
+        if self.level_tolerance {
+            (a - b).abs() < 0.5 / self.precision.price_factor()
+        } else {
+            a == b
+        }
+    }
+
+    /// Last 1-based price level of the given side that can contribute to the checksum.
+    ///
+    /// Every skipped ask level within the levels reached so far extends the asks by one level,
+    /// skipped levels beyond them and repeated entries don't.
+    pub fn last_level(&self, side: Side) -> usize {
+        if side == Side::Bid {
+            return self.depth;
+        }
+        let mut last = self.depth;
+        loop {
+            let skipped = self
+                .skip_ask_levels
+                .iter()
+                .enumerate()
+                .filter(|(index, level)| {
+                    (1..=last).contains(*level) && !self.skip_ask_levels[..*index].contains(level)
+                })
+                .count();
+            if self.depth + skipped == last {
+                return last;
+            }
+            last = self.depth + skipped;
+        }
+    }
+}
+
+impl Default for ChecksumOptions {
+    fn default() -> Self {
+        ChecksumOptions {
+            precision: Precision::default(),
+            depth: 10,
+            skip_ask_levels: Vec::new(),
+            rounding: RoundingMode::default(),
+            strict_precision: true,
+            version: ChecksumVersion::default(),
+            level_tolerance: true,
+        }
+    }
+}
+
+/// Computes the CRC32 checksum over the top price levels of the book (asks first, then bids).
+///
+/// The precision is looked up by the data's symbol, see `ChecksumOptions::for_symbol`.
+///
+/// An empty side is skipped and contributes no digits, Kraken sends one-sided books e.g. during
+/// illiquid opens.
+///
+/// A price level is a run of consecutive orders with the same price. It counts once towards the
+/// depth, however many orders it holds, and every one of its orders adds its price and qty
+/// digits in feed order, the timestamps are not looked at (so a level whose orders are not in
+/// time order is hashed as is). The ask levels 10 and 11 that Kraken leaves out of the checksum
+/// of `level3-bug.json` are single orders, the quirk is unrelated to multi-order levels.
+///
+/// Panics if a price or qty does not fit the assumed precision, see `try_compute_checksum`.
+pub fn compute_checksum(data: &Level3Data) -> u32 {
+    checksum_of(
+        &data.asks,
+        &data.bids,
+        &ChecksumOptions::for_symbol(&data.symbol),
+    )
+}
+
+pub fn try_compute_checksum(data: &Level3Data, opts: &ChecksumOptions) -> Result<u32, Level3Error> {
+    let _span = trace_span!("checksum", symbol = %data.symbol).entered();
+    try_checksum_of(&data.asks, &data.bids, opts)
+}
+
+/// Checksum over orders kept outside of a `Level3Data`, e.g. in an own book representation. The
+/// asks are expected in ascending and the bids in descending price order, like the feed lists
+/// them.
+///
+/// Panics if a price or qty does not fit the precision of the options, see `try_checksum_of`.
+pub fn checksum_of(asks: &[Order], bids: &[Order], opts: &ChecksumOptions) -> u32 {
+    try_checksum_of(asks, bids, opts).unwrap()
+}
+
+pub fn try_checksum_of(
+    asks: &[Order],
+    bids: &[Order],
+    opts: &ChecksumOptions,
+) -> Result<u32, Level3Error> {
+    Ok(Crc32.checksum(input_of(asks, bids, opts)?.as_bytes()))
+}
+
+/// Computes the checksum from orders pushed one at a time, e.g. by a parser that never holds the
+/// whole book. `finish` gives the same checksum as `checksum_of` over the pushed orders.
+///
+/// Each side must be pushed in book order, the asks by ascending and the bids by descending
+/// price, the way the feed lists them: asks first, each side sorted. The sides are kept apart,
+/// so pushing asks and bids interleaved works as well. Only the orders on the levels up to
+/// `ChecksumOptions::last_level` are kept, the ones after are dropped as they are pushed.
+#[derive(Debug, Clone)]
+pub struct ChecksumBuilder {
+    opts: ChecksumOptions,
+    asks: LevelBuffer,
+    bids: LevelBuffer,
+}
+
+impl ChecksumBuilder {
+    pub fn new(opts: ChecksumOptions) -> Self {
+        ChecksumBuilder {
+            opts,
+            asks: LevelBuffer::default(),
+            bids: LevelBuffer::default(),
+        }
+    }
+
+    pub fn push_ask(&mut self, order: &Order) {
+        self.asks.push(order.clone(), Side::Ask, &self.opts);
+    }
+
+    pub fn push_bid(&mut self, order: &Order) {
+        self.bids.push(order.clone(), Side::Bid, &self.opts);
+    }
+
+    /// Panics if a pushed price or qty does not fit the precision of the options, see
+    /// `try_finish`.
+    pub fn finish(self) -> u32 {
+        self.try_finish().unwrap()
+    }
+
+    pub fn try_finish(self) -> Result<u32, Level3Error> {
+        try_checksum_of(&self.asks.orders, &self.bids.orders, &self.opts)
+    }
+}
+
+// the orders of one side on the levels up to `last_level`, levels are counted like
+// `collect_levels` does
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LevelBuffer {
+    pub(crate) orders: Vec<Order>,
+    level_price: Option<f64>,
+    price_level_count: usize,
+}
+
+impl LevelBuffer {
+    // false if the order is beyond the last level and dropped, so are all later orders of the side
+    pub(crate) fn push(&mut self, order: Order, side: Side, opts: &ChecksumOptions) -> bool {
+        if !self
+            .level_price
+            .is_some_and(|price| opts.same_price(order.limit_price, price))
+        {
+            self.level_price = Some(order.limit_price);
+            self.price_level_count += 1;
+        }
+        if self.price_level_count > opts.last_level(side) {
+            return false;
+        }
+        self.orders.push(order);
+        true
+    }
+}
+
+/// The hash over the CRC input string, the input is built the same way whatever the hash.
+///
+/// Kraken uses CRC32 (`Crc32`), another implementation can be plugged into
+/// `try_compute_checksum_with` to experiment or to follow a change of the protocol.
+pub trait Checksummer {
+    type Output;
+
+    fn checksum(&self, input: &[u8]) -> Self::Output;
+}
+
+/// Kraken's CRC32, computed with `crc32fast`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Crc32;
+
+impl Checksummer for Crc32 {
+    type Output = u32;
+
+    fn checksum(&self, input: &[u8]) -> u32 {
+        crc32fast::hash(input)
+    }
+}
+
+/// Like `try_compute_checksum` with the given hash instead of CRC32.
+pub fn try_compute_checksum_with<H: Checksummer>(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+    checksummer: &H,
+) -> Result<H::Output, Level3Error> {
+    Ok(checksummer.checksum(try_checksum_input_string(data, opts)?.as_bytes()))
+}
+
+/// True if the checksum computed with the symbol's options (`ChecksumOptions::for_symbol`)
+/// equals the one embedded in the data.
+///
+/// A price or qty that doesn't fit the precision counts as a mismatch.
+pub fn checksum_matches(data: &Level3Data) -> bool {
+    verify_checksum(data).is_ok()
+}
+
+/// Compares the checksum computed with the symbol's options against the one embedded in the data,
+/// a difference is reported as `Level3Error::ChecksumMismatch`.
+pub fn verify_checksum(data: &Level3Data) -> Result<(), Level3Error> {
+    verify_against(data, data.checksum)
+}
+
+/// Like `verify_checksum` with the expected checksum supplied by the caller, for transports that
+/// send it outside of the per-symbol data (the embedded `checksum` field is ignored).
+pub fn verify_against(data: &Level3Data, expected: u32) -> Result<(), Level3Error> {
+    let computed = try_compute_checksum(data, &ChecksumOptions::for_symbol(&data.symbol))?;
+    if computed != expected {
+        return Err(Level3Error::ChecksumMismatch { expected, computed });
+    }
+    Ok(())
+}
+
+/// Replaces the embedded checksum with the one computed with the symbol's options, e.g. after
+/// orders were removed with `filter_stale` or `truncate`.
+pub fn refresh_checksum(data: &mut Level3Data) -> Result<(), Level3Error> {
+    data.checksum = try_compute_checksum(data, &ChecksumOptions::for_symbol(&data.symbol))?;
+    Ok(())
+}
+
+/// Returns the concatenated price/qty digits that are fed into the CRC32 hash.
+pub fn checksum_input_string(data: &Level3Data) -> String {
+    try_checksum_input_string(data, &ChecksumOptions::for_symbol(&data.symbol)).unwrap()
+}
+
+pub fn try_checksum_input_string(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> Result<String, Level3Error> {
+    let _span = trace_span!("checksum", symbol = %data.symbol).entered();
+    input_of(&data.asks, &data.bids, opts)
+}
+
+// the asks followed by the bids
+fn input_of(asks: &[Order], bids: &[Order], opts: &ChecksumOptions) -> Result<String, Level3Error> {
+    let mut crc_str = String::new();
+    for level_str in side_levels(asks, Side::Ask, opts)? {
+        crc_str.push_str(&level_str);
+    }
+    for level_str in side_levels(bids, Side::Bid, opts)? {
+        crc_str.push_str(&level_str);
+    }
+    Ok(crc_str)
+}
+
+/// A price or qty that doesn't fit the checksum precision, the checksum was computed with the
+/// value rounded to the nearest digits anyway.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PrecisionWarning {
+    pub order_id: String,
+    pub field: &'static str,
+    pub value: f64,
+    /// Scaled value minus the rounded digits, e.g. `0.5` for a price of `44939.45` at one decimal.
+    pub residual: f64,
+}
+
+impl Display for PrecisionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} of order {} does not fit the assumed precision (residual {})",
+            self.field, self.value, self.order_id, self.residual
+        )
+    }
+}
+
+/// Like `try_compute_checksum` but a value that doesn't fit the precision is rounded and reported
+/// as a warning instead of failing the whole checksum.
+///
+/// Negative values aren't checked here, `try_compute_checksum` rejects them.
+pub fn checksum_with_warnings(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> (u32, Vec<PrecisionWarning>) {
+    let (crc_str, warnings) = checksum_input_with_warnings(data, opts);
+    (crc32fast::hash(crc_str.as_bytes()), warnings)
+}
+
+/// CRC input string of `checksum_with_warnings`.
+pub fn checksum_input_with_warnings(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> (String, Vec<PrecisionWarning>) {
+    let precision = &opts.precision;
+    let warnings = RefCell::new(Vec::new());
+    let scale = |order: &Order, field: &'static str, value: f64, decimals: u32| {
+        let warn = |residual| {
+            warnings.borrow_mut().push(PrecisionWarning {
+                order_id: order.order_id.clone(),
+                field,
+                value,
+                residual,
+            })
+        };
+        // without a factor nothing is scaled, the value is written as is
+        let Some(factor) = factor(decimals) else {
+            warn(f64::INFINITY);
+            return format_digits(value, decimals);
+        };
+        let value_f = value * factor;
+        let residual = value_f - value_f.round();
+        if residual.abs() >= 1e-3 {
+            warn(residual);
+        }
+        format_digits(value_f.round() / factor, decimals)
+    };
+    let mut crc_str = String::new();
+    for side in [Side::Ask, Side::Bid] {
+        let levels = collect_levels(
+            data.orders(side),
+            side,
+            opts,
+            |order| order.limit_price,
+            |a, b| opts.same_price(*a, *b),
+            |total, order| total.order_qty += order.order_qty,
+            |_, order| {
+                let price_s = scale(order, "price", order.limit_price, precision.price_decimals);
+                let qty_s = scale(order, "qty", order.order_qty, precision.qty_decimals);
+                Ok(price_s + &qty_s)
+            },
+        )
+        .unwrap();
+        crc_str.push_str(&levels.concat());
+    }
+    (crc_str, warnings.into_inner())
+}
+
+/// CRC32 over the top `depth` price levels of a single side, with the symbol's precision.
+///
+/// This is a diagnostic aid to find out which side diverges, it is NOT the checksum Kraken sends:
+/// the official checksum always hashes the asks followed by the bids (see `compute_checksum`).
+///
+/// Panics if a price or qty does not fit the assumed precision, see `try_checksum_side`.
+pub fn checksum_side(data: &Level3Data, side: Side, depth: usize) -> u32 {
+    let opts = ChecksumOptions {
+        depth,
+        ..ChecksumOptions::for_symbol(&data.symbol)
+    };
+    try_checksum_side(data, side, &opts).unwrap()
+}
+
+/// CRC32 over the contributing price levels of a single side with the given options.
+pub fn try_checksum_side(
+    data: &Level3Data,
+    side: Side,
+    opts: &ChecksumOptions,
+) -> Result<u32, Level3Error> {
+    let levels = side_levels(data.orders(side), side, opts)?;
+    Ok(crc32fast::hash(levels.concat().as_bytes()))
+}
+
+/// Compares the generated CRC input level by level against an expected input string and returns
+/// the side and (zero based) level index of the first level that diverges.
+///
+/// Returns `None` if both strings are equal. If the expected string has additional digits after
+/// the generated ones, the index one past the last bid level is reported.
+///
+/// Panics if a price or qty does not fit the assumed precision, see `try_checksum_diff`.
+pub fn checksum_diff(data: &Level3Data, expected_input: &str) -> Option<(Side, usize)> {
+    try_checksum_diff(
+        data,
+        &ChecksumOptions::for_symbol(&data.symbol),
+        expected_input,
+    )
+    .unwrap()
+}
+
+/// Like `checksum_diff`, the input is generated with the given options.
+pub fn try_checksum_diff(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+    expected_input: &str,
+) -> Result<Option<(Side, usize)>, Level3Error> {
+    let asks = side_levels(data.orders(Side::Ask), Side::Ask, opts)?;
+    let bids = side_levels(data.orders(Side::Bid), Side::Bid, opts)?;
+
+    let mut rest = expected_input;
+    let levels = asks
+        .iter()
+        .enumerate()
+        .map(|(index, level_str)| (Side::Ask, index, level_str))
+        .chain(
+            bids.iter()
+                .enumerate()
+                .map(|(index, level_str)| (Side::Bid, index, level_str)),
+        );
+    for (side, index, level_str) in levels {
+        match rest.strip_prefix(level_str.as_str()) {
+            Some(remaining) => rest = remaining,
+            None => return Ok(Some((side, index))),
+        }
+    }
+    if rest.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some((Side::Bid, bids.len())))
+    }
+}
+
+/// Outcome of `checksum_near`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ChecksumComparison {
+    pub checksum_a: u32,
+    pub checksum_b: u32,
+    /// Byte position of the first character in which the CRC inputs differ, the length of the
+    /// shorter one if it is a prefix of the other. `None` if the inputs are equal.
+    pub first_difference: Option<usize>,
+    /// Side and (zero based) level of `b` at which its input diverges, see `checksum_diff`.
+    pub level: Option<(Side, usize)>,
+}
+
+impl ChecksumComparison {
+    pub fn inputs_equal(&self) -> bool {
+        self.first_difference.is_none()
+    }
+}
+
+/// Compares the CRC inputs of two books with their symbols' options, e.g. a book rebuilt from
+/// float updates against the snapshot it should equal.
+///
+/// Equal inputs mean the books only differ below the checksum precision or beyond its depth,
+/// float drift that scales to the same digits. A difference points at a level whose digits
+/// really changed, unlike `diff` it's found on the hashed string rather than by order id.
+///
+/// Panics if a price or qty does not fit the assumed precision.
+pub fn checksum_near(a: &Level3Data, b: &Level3Data) -> ChecksumComparison {
+    let input_a = checksum_input_string(a);
+    let input_b = checksum_input_string(b);
+    let first_difference = if input_a == input_b {
+        None
+    } else {
+        Some(
+            input_a
+                .bytes()
+                .zip(input_b.bytes())
+                .take_while(|(a, b)| a == b)
+                .count(),
+        )
+    };
+    ChecksumComparison {
+        checksum_a: crc32fast::hash(input_a.as_bytes()),
+        checksum_b: crc32fast::hash(input_b.as_bytes()),
+        first_difference,
+        level: checksum_diff(b, &input_a),
+    }
+}
+
+/// One price/qty pair of the checksum input, see `explain_checksum`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ChecksumEntry {
+    pub side: Side,
+    /// Zero based price level of the order, skipped levels are counted as well.
+    pub level: usize,
+    pub price_string: String,
+    pub qty_string: String,
+    /// The CRC input up to and including this entry.
+    pub crc_input: String,
+}
+
+/// Every price/qty pair that enters the checksum with the symbol's options, see
+/// `try_explain_checksum`.
+///
+/// Panics if a price or qty does not fit the assumed precision.
+pub fn explain_checksum(data: &Level3Data) -> Vec<ChecksumEntry> {
+    try_explain_checksum(data, &ChecksumOptions::for_symbol(&data.symbol)).unwrap()
+}
+
+/// Every price/qty pair that enters the checksum in input order, asks first, then bids. With
+/// `ChecksumVersion::V1` there is one entry per price level with its total qty.
+///
+/// The `crc_input` of the last entry is the whole `try_checksum_input_string`.
+pub fn try_explain_checksum(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> Result<Vec<ChecksumEntry>, Level3Error> {
+    let pairs = RefCell::new(Vec::new());
+    for side in [Side::Ask, Side::Bid] {
+        collect_levels(
+            data.orders(side),
+            side,
+            opts,
+            |order| order.limit_price,
+            |a, b| opts.same_price(*a, *b),
+            |total, order| total.order_qty += order.order_qty,
+            |level, order| {
+                let (price_s, qty_s) = digit_pair(order, opts)?;
+                let digits = price_s.clone() + &qty_s;
+                pairs.borrow_mut().push((side, level - 1, price_s, qty_s));
+                Ok(digits)
+            },
+        )?;
+    }
+    let mut crc_input = String::new();
+    Ok(pairs
+        .into_inner()
+        .into_iter()
+        .map(|(side, level, price_string, qty_string)| {
+            crc_input.push_str(&price_string);
+            crc_input.push_str(&qty_string);
+            ChecksumEntry {
+                side,
+                level,
+                price_string,
+                qty_string,
+                crc_input: crc_input.clone(),
+            }
+        })
+        .collect())
+}
+
+/// The price and qty digits the order adds to the CRC input with the symbol's options, `None` if
+/// the order id isn't in the book or its level is beyond the checksum depth (or skipped).
+///
+/// Panics if the order's price or qty does not fit the assumed precision.
+pub fn order_checksum_contribution(data: &Level3Data, order_id: &str) -> Option<String> {
+    let opts = ChecksumOptions::for_symbol(&data.symbol);
+    let (side, level) = order_rank(data, order_id)?;
+    if !opts.includes_level(side, level + 1) {
+        return None;
+    }
+    let order = data
+        .orders(side)
+        .iter()
+        .find(|order| order.order_id == order_id)?;
+    let (price_s, qty_s) = digit_pair(order, &opts).unwrap();
+    Some(price_s + &qty_s)
+}
+
+/// The ask and bid orders whose digits enter the CRC input with `opts`, in input order. With
+/// `ChecksumVersion::V1` these are all orders of the contributing levels, their qtys are summed
+/// into one entry per level.
+pub fn checksum_orders<'a>(
+    data: &'a Level3Data,
+    opts: &ChecksumOptions,
+) -> (Vec<&'a Order>, Vec<&'a Order>) {
+    (
+        contributing_orders(&data.asks, Side::Ask, opts),
+        contributing_orders(&data.bids, Side::Bid, opts),
+    )
+}
+
+// the orders on the levels `collect_levels` takes the digits of
+fn contributing_orders<'a>(
+    orders: &'a [Order],
+    side: Side,
+    select: &impl LevelSelection,
+) -> Vec<&'a Order> {
+    let opts = select.options();
+    let mut contributing = Vec::new();
+    let mut level_price = None;
+    let mut price_level_count = 0;
+    for order in orders {
+        if !level_price.is_some_and(|price| opts.same_price(order.limit_price, price)) {
+            level_price = Some(order.limit_price);
+            price_level_count += 1;
+            if price_level_count > select.last_level(side) {
+                break;
+            }
+        }
+        if select.includes(side, price_level_count) {
+            contributing.push(order);
+        }
+    }
+    contributing
+}
+
+/// Checksum with the precision, rounding and version of `opts` over the price levels that
+/// `include` selects, `depth` and `skip_ask_levels` of the options are not used.
+///
+/// `include(side, level)` gets the 1-based price level of a side and decides whether all orders
+/// of that level contribute. It is called once per level in book order, the asks before the
+/// bids, and for every level of the side since there is no depth to stop at: it should be cheap
+/// and must return `false` beyond the levels meant to contribute. The result may only depend on
+/// the side and level, levels counted by `ChecksumOptions::same_price`.
+///
+/// `|side, level| opts.includes_level(side, level)` gives the same checksum as
+/// `try_compute_checksum`, the top 10 levels per side with the default options. Kraken's ask
+/// level quirk of `ChecksumOptions::level3_bug` takes ask levels 1 to 9 and 12 and bid levels
+/// 1 to 10.
+pub fn try_checksum_with_levels(
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+    include: impl Fn(Side, usize) -> bool,
+) -> Result<u32, Level3Error> {
+    let select = Predicate { opts, include };
+    let mut crc_str = String::new();
+    for side in [Side::Ask, Side::Bid] {
+        crc_str.push_str(&side_levels(data.orders(side), side, &select)?.concat());
+    }
+    Ok(crc32fast::hash(crc_str.as_bytes()))
+}
+
+// which price levels contribute: the depth and skipped levels of the options,
+// or the predicate of `try_checksum_with_levels`
+pub(crate) trait LevelSelection {
+    fn options(&self) -> &ChecksumOptions;
+
+    fn includes(&self, side: Side, level: usize) -> bool;
+
+    // the walk over a side stops after this level
+    fn last_level(&self, side: Side) -> usize;
+}
+
+impl LevelSelection for ChecksumOptions {
+    fn options(&self) -> &ChecksumOptions {
+        self
+    }
+
+    fn includes(&self, side: Side, level: usize) -> bool {
+        self.includes_level(side, level)
+    }
+
+    fn last_level(&self, side: Side) -> usize {
+        ChecksumOptions::last_level(self, side)
+    }
+}
+
+struct Predicate<'a, F> {
+    opts: &'a ChecksumOptions,
+    include: F,
+}
+
+impl<F: Fn(Side, usize) -> bool> LevelSelection for Predicate<'_, F> {
+    fn options(&self) -> &ChecksumOptions {
+        self.opts
+    }
+
+    fn includes(&self, side: Side, level: usize) -> bool {
+        (self.include)(side, level)
+    }
+
+    fn last_level(&self, _side: Side) -> usize {
+        usize::MAX
+    }
+}
+
+// digit strings of the contributing price levels of one side, one entry per price level
+pub(crate) fn side_levels(
+    orders: &[Order],
+    side: Side,
+    select: &impl LevelSelection,
+) -> Result<Vec<String>, Level3Error> {
+    let opts = select.options();
+    collect_levels(
+        orders,
+        side,
+        select,
+        |order| order.limit_price,
+        |a, b| opts.same_price(*a, *b),
+        |total, order| total.order_qty += order.order_qty,
+        |_, order| {
+            let (price_s, qty_s) = digit_pair(order, opts)?;
+            Ok(price_s + &qty_s)
+        },
+    )
+}
+
+// price and qty digits of one order
+fn digit_pair(order: &Order, opts: &ChecksumOptions) -> Result<(String, String), Level3Error> {
+    let precision = &opts.precision;
+    let price_s = order_digits(
+        order,
+        "price",
+        order.limit_price,
+        precision.price_decimals,
+        opts,
+    )?;
+    let qty_s = order_digits(order, "qty", order.order_qty, precision.qty_decimals, opts)?;
+    Ok((price_s, qty_s))
+}
+
+// walks the price levels of one side and collects the digits of the orders on contributing levels,
+// every order is traced with its level so mismatches can be narrowed down with RUST_LOG=trace.
+// For ChecksumVersion::V1 the orders of a level are folded into one entry with `merge_qty` first.
+// `digits` gets the 1-based price level along with the entry.
+pub(crate) fn collect_levels<T: Clone, P: Debug>(
+    orders: &[T],
+    side: Side,
+    select: &impl LevelSelection,
+    price: impl Fn(&T) -> P,
+    same_price: impl Fn(&P, &P) -> bool,
+    merge_qty: impl Fn(&mut T, &T),
+    digits: impl Fn(usize, &T) -> Result<String, Level3Error>,
+) -> Result<Vec<String>, Level3Error> {
+    let mut levels: Vec<String> = Vec::new();
+    let mut rest = orders;
+    let mut price_level_count = 0;
+    while let Some(first) = rest.first() {
+        let level_price = price(first);
+        // a NaN price doesn't equal itself, it still forms a level of its own
+        let order_count = rest
+            .iter()
+            .take_while(|order| same_price(&price(order), &level_price))
+            .count()
+            .max(1);
+        let (level, tail) = rest.split_at(order_count);
+        rest = tail;
+        price_level_count += 1;
+        if price_level_count > select.last_level(side) {
+            break;
+        }
+        if !select.includes(side, price_level_count) {
+            trace!(?side, level = price_level_count, price = ?level_price, "skipped");
+            continue;
+        }
+
+        let merged;
+        let entries = match select.options().version {
+            ChecksumVersion::V2 => level,
+            ChecksumVersion::V1 => {
+                let mut total = first.clone();
+                for order in &level[1..] {
+                    merge_qty(&mut total, order);
+                }
+                merged = [total];
+                &merged[..]
+            }
+        };
+        let mut level_str = String::new();
+        for entry in entries {
+            let entry_digits = digits(price_level_count, entry)?;
+            trace!(
+                ?side,
+                level = price_level_count,
+                price = ?level_price,
+                digits = %entry_digits,
+                "included"
+            );
+            level_str.push_str(&entry_digits);
+        }
+        levels.push(level_str);
+    }
+    Ok(levels)
+}
+
+/// Formats a price or qty the way it enters the checksum: the value is written with `decimals`
+/// decimals, the decimal point is removed and leading zeros are stripped (`0.00100000` becomes
+/// `100000`). A value of zero is kept as `"0"` rather than an empty string.
+///
+/// Returns `None` if the value has significant digits beyond `decimals` or is negative, prices
+/// and quantities in the feed never are, and if `10^decimals` doesn't fit an `u64`.
+pub fn checksum_digits(value: f64, decimals: u32) -> Option<String> {
+    if value < 0.0 || !fits_precision(scaled(value, decimals)?) {
+        return None;
+    }
+    Some(format_digits(value, decimals))
+}
+
+/// Like `checksum_digits`, a value with significant digits beyond `decimals` is scaled with the
+/// given rounding mode instead of being rejected.
+///
+/// Values that fit the precision give the same digits in every mode, the float error of the
+/// scaling (`4.35 * 100.0` is `434.99999999999994`) never shifts them to a neighbouring value.
+pub fn checksum_digits_with(value: f64, decimals: u32, rounding: RoundingMode) -> Option<String> {
+    if value < 0.0 {
+        return None;
+    }
+    let value_f = scaled(value, decimals)?;
+    if !fits_precision(value_f) {
+        let scaled = match rounding {
+            RoundingMode::Round => value_f.round(),
+            RoundingMode::Truncate => value_f.trunc(),
+            RoundingMode::Ceil => value_f.ceil(),
+            RoundingMode::Floor => value_f.floor(),
+        };
+        // adding 0.0 turns a negative zero into zero
+        return Some(format!("{:.0}", scaled + 0.0));
+    }
+    Some(format_digits(value, decimals))
+}
+
+// `10^decimals`, `None` if it doesn't fit an `u64` like in `scaled_digits`
+fn factor(decimals: u32) -> Option<f64> {
+    Some(10u64.checked_pow(decimals)? as f64)
+}
+
+// the value scaled by `10^decimals`
+fn scaled(value: f64, decimals: u32) -> Option<f64> {
+    Some(value * factor(decimals)?)
+}
+
+// whether the scaled value has no significant digits beyond the precision, allowing for the
+// float error of the scaling
+fn fits_precision(value_f: f64) -> bool {
+    (value_f - value_f.round()).abs() < 1e-3
+}
+
+// writes the value with `decimals` decimals, without the decimal point and leading zeros
+fn format_digits(value: f64, decimals: u32) -> String {
+    // adding 0.0 turns a negative zero, which serde reads from "-0.0", into zero, it would be
+    // written as "-0" otherwise
+    let formatted = format!("{:.*}", decimals as usize, value + 0.0);
+    let digits: String = formatted.chars().filter(|c| *c != '.').collect();
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        "0".to_string()
+    } else {
+        digits.to_string()
+    }
+}
+
+// negative values and values that overflow when scaled are reported as such rather than as a
+// precision error
+fn order_digits(
+    order: &Order,
+    field: &'static str,
+    value: f64,
+    decimals: u32,
+    opts: &ChecksumOptions,
+) -> Result<String, Level3Error> {
+    if value < 0.0 {
+        return Err(Level3Error::Negative {
+            order_id: order.order_id.clone(),
+            field,
+            value,
+        });
+    }
+    // the scaled value has to fit an i64 like in the decimal checksum, this also catches NaN
+    // and infinity
+    let overflow = || Level3Error::Overflow {
+        order_id: order.order_id.clone(),
+    };
+    let factor = 10i64.checked_pow(decimals).ok_or_else(overflow)?;
+    let scaled = (value * factor as f64).round();
+    if !scaled.is_finite() || scaled >= i64::MAX as f64 {
+        return Err(overflow());
+    }
+    let digits = if opts.strict_precision {
+        checksum_digits(value, decimals)
+    } else {
+        checksum_digits_with(value, decimals, opts.rounding)
+    };
+    digits.ok_or_else(|| Level3Error::Precision {
+        order_id: order.order_id.clone(),
+        field,
+        value,
+    })
+}
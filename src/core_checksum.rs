@@ -0,0 +1,145 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The checksum math on plain prices and quantities, usable without `std`.
+//!
+//! Nothing here reads files, parses JSON or allocates, the digits of every price and qty are
+//! hashed as they are produced. The std API (`Level3Data`, `compute_checksum`, ...) is behind
+//! the `std` feature, which is on by default.
+
+/// Number of decimals used to scale prices and quantities into the integer digits of the checksum.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Precision {
+    pub price_decimals: u32,
+    pub qty_decimals: u32,
+}
+
+impl Precision {
+    /// `10^price_decimals`, infinite if that doesn't fit an `u64`, scaling by it then overflows.
+    pub fn price_factor(&self) -> f64 {
+        factor(self.price_decimals)
+    }
+
+    /// `10^qty_decimals`, infinite if that doesn't fit an `u64`.
+    pub fn qty_factor(&self) -> f64 {
+        factor(self.qty_decimals)
+    }
+}
+
+fn factor(decimals: u32) -> f64 {
+    10u64
+        .checked_pow(decimals)
+        .map_or(f64::INFINITY, |factor| factor as f64)
+}
+
+impl Default for Precision {
+    // matches the sampled BTC/USD pair
+    fn default() -> Self {
+        Precision {
+            price_decimals: 1,
+            qty_decimals: 8,
+        }
+    }
+}
+
+/// Price and qty of one order, all that enters the checksum.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct RawOrder {
+    pub price: f64,
+    pub qty: f64,
+}
+
+/// The value scaled by `10^decimals`, the integer whose digits enter the checksum.
+///
+/// Returns `None` for negative values, values with significant digits beyond `decimals` and
+/// values that don't fit an `u64` once scaled.
+pub fn scaled_digits(value: f64, decimals: u32) -> Option<u64> {
+    if value.is_nan() || value < 0.0 {
+        return None;
+    }
+    let value_f = value * 10u64.checked_pow(decimals)? as f64;
+    // 2^63, the u64 conversion below saturates beyond it
+    if value_f >= 9_223_372_036_854_775_808.0 {
+        return None;
+    }
+    let scaled = (value_f + 0.5) as u64;
+    let residual = value_f - scaled as f64;
+    if residual >= 1e-3 || residual <= -1e-3 {
+        return None;
+    }
+    Some(scaled)
+}
+
+/// Kraken's v2 checksum over the top `depth` price levels of each side: the asks in ascending
+/// and the bids in descending price order, as the feed lists them.
+///
+/// Consecutive orders with the same price form a level. Returns `None` if a price or qty doesn't
+/// fit the precision (see `scaled_digits`).
+///
+/// Gives the checksum of the std API's `try_compute_checksum` with the default `ChecksumOptions`
+/// at this precision and depth as long as the orders of a level carry identical prices: levels
+/// are split on exact price equality here, the std API merges prices closer than half a price
+/// unit (`ChecksumOptions::level_tolerance`). Kraken's ask level quirk
+/// (`ChecksumOptions::level3_bug`) is not applied either.
+pub fn raw_checksum(
+    asks: &[RawOrder],
+    bids: &[RawOrder],
+    precision: Precision,
+    depth: usize,
+) -> Option<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    for orders in [asks, bids] {
+        let mut price_level_count = 0;
+        let mut curr_price: Option<f64> = None;
+        for order in orders {
+            if curr_price != Some(order.price) {
+                curr_price = Some(order.price);
+                price_level_count += 1;
+            }
+            if price_level_count > depth {
+                break;
+            }
+            hash_digits(
+                &mut hasher,
+                scaled_digits(order.price, precision.price_decimals)?,
+            );
+            hash_digits(
+                &mut hasher,
+                scaled_digits(order.qty, precision.qty_decimals)?,
+            );
+        }
+    }
+    Some(hasher.finalize())
+}
+
+// feeds the decimal digits of the value without leading zeros, zero is hashed as "0"
+fn hash_digits(hasher: &mut crc32fast::Hasher, mut value: u64) {
+    let mut buf = [0u8; 20];
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    hasher.update(&buf[start..]);
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::str::FromStr;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::value::RawValue;
+use serde_with::skip_serializing_none;
+
+use crate::checksum::{collect_levels, ChecksumOptions};
+use crate::error::Level3Error;
+use crate::level3::{snapshot_data, timestamp, OrderEvent, Side};
+
+/// Variant of `Level3Data` that keeps prices and quantities as exact decimals, so the checksum
+/// digits are extracted by integer scaling instead of float rounding.
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DecimalLevel3Data {
+    pub symbol: String,
+    pub bids: Vec<DecimalOrder>,
+    pub asks: Vec<DecimalOrder>,
+    pub checksum: u32,
+}
+
+impl DecimalLevel3Data {
+    pub fn orders(&self, side: Side) -> &[DecimalOrder] {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DecimalOrder {
+    pub event: Option<OrderEvent>,
+    pub order_id: String,
+    #[serde(deserialize_with = "raw_decimal")]
+    pub limit_price: Decimal,
+    #[serde(deserialize_with = "raw_decimal")]
+    pub order_qty: Decimal,
+    #[serde(
+        serialize_with = "time::serde::rfc3339::serialize",
+        deserialize_with = "timestamp"
+    )]
+    pub timestamp: time::OffsetDateTime,
+}
+
+// parses the JSON token as written (number or string) so no float conversion is involved,
+// this needs the serde_json deserializer and doesn't work from a `Value`
+fn raw_decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let raw = Box::<RawValue>::deserialize(deserializer)?;
+    let token = raw.get().trim_matches('"');
+    Decimal::from_str(token)
+        .or_else(|_| Decimal::from_scientific(token))
+        .map_err(serde::de::Error::custom)
+}
+
+/// Parses a level3 snapshot message keeping prices and quantities as decimals.
+pub fn parse_decimal_snapshot(line_str: &str) -> Result<Vec<DecimalLevel3Data>, Level3Error> {
+    snapshot_data(serde_json::from_str(line_str)?)
+}
+
+pub fn compute_decimal_checksum(
+    data: &DecimalLevel3Data,
+    opts: &ChecksumOptions,
+) -> Result<u32, Level3Error> {
+    Ok(crc32fast::hash(
+        decimal_checksum_input_string(data, opts)?.as_bytes(),
+    ))
+}
+
+pub fn decimal_checksum_input_string(
+    data: &DecimalLevel3Data,
+    opts: &ChecksumOptions,
+) -> Result<String, Level3Error> {
+    let _span = tracing::trace_span!("decimal_checksum", symbol = %data.symbol).entered();
+    let mut crc_str = String::new();
+    for side in [Side::Ask, Side::Bid] {
+        let levels = collect_levels(
+            data.orders(side),
+            side,
+            opts,
+            |order| order.limit_price,
+            |a, b| a == b,
+            |total, order| total.order_qty += order.order_qty,
+            |_, order| {
+                let price_s = scale(
+                    order,
+                    "price",
+                    order.limit_price,
+                    opts.precision.price_decimals,
+                )?;
+                let qty_s = scale(order, "qty", order.order_qty, opts.precision.qty_decimals)?;
+                Ok(price_s + &qty_s)
+            },
+        )?;
+        for level_str in levels {
+            crc_str.push_str(&level_str);
+        }
+    }
+    Ok(crc_str)
+}
+
+// shifts the value by the given decimals, the result must be an integer that fits an i64
+fn scale(
+    order: &DecimalOrder,
+    field: &'static str,
+    value: Decimal,
+    decimals: u32,
+) -> Result<String, Level3Error> {
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(Level3Error::Negative {
+            order_id: order.order_id.clone(),
+            field,
+            value: value.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+    let overflow = || Level3Error::Overflow {
+        order_id: order.order_id.clone(),
+    };
+    let factor = 10i64.checked_pow(decimals).ok_or_else(overflow)?;
+    let scaled = value
+        .checked_mul(Decimal::from(factor))
+        .ok_or_else(overflow)?;
+    if !scaled.fract().is_zero() {
+        return Err(Level3Error::Precision {
+            order_id: order.order_id.clone(),
+            field,
+            value: value.to_f64().unwrap_or(f64::NAN),
+        });
+    }
+    Ok(scaled.to_i64().ok_or_else(overflow)?.to_string())
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+
+use crate::level3::{Level3Data, Order, OrderEvent, Side};
+
+/// Order level changes between two snapshots of the same book.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct BookDiff {
+    /// Orders only in the new snapshot, in feed order.
+    pub added: Vec<OrderChange>,
+    /// Orders in both snapshots whose price, qty or timestamp changed, in feed order.
+    pub modified: Vec<OrderChange>,
+    /// Orders only in the old snapshot, in feed order.
+    pub removed: Vec<OrderChange>,
+}
+
+impl BookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A changed order with its qty before and after, `None` where the order didn't exist.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OrderChange {
+    pub side: Side,
+    pub order_id: String,
+    pub before_qty: Option<f64>,
+    pub after_qty: Option<f64>,
+}
+
+/// Compares two snapshots by order id, i.e. the add/modify/delete events that lead from `old`
+/// to `new`.
+///
+/// An order id that moved to the other side is reported as removed and added.
+pub fn diff(old: &Level3Data, new: &Level3Data) -> BookDiff {
+    let old_index = order_index(old);
+    let new_index = order_index(new);
+    let mut book_diff = BookDiff::default();
+
+    for (side, order) in orders(new) {
+        let change = |before: Option<&Order>| OrderChange {
+            side,
+            order_id: order.order_id.clone(),
+            before_qty: before.map(|before| before.order_qty),
+            after_qty: Some(order.order_qty),
+        };
+        match old_index.get(order.order_id.as_str()) {
+            Some((old_side, before)) if *old_side == side => {
+                if before.limit_price != order.limit_price
+                    || before.order_qty != order.order_qty
+                    || before.timestamp != order.timestamp
+                {
+                    book_diff.modified.push(change(Some(before)));
+                }
+            }
+            _ => book_diff.added.push(change(None)),
+        }
+    }
+    for (side, order) in orders(old) {
+        match new_index.get(order.order_id.as_str()) {
+            Some((new_side, _)) if *new_side == side => {}
+            _ => book_diff.removed.push(OrderChange {
+                side,
+                order_id: order.order_id.clone(),
+                before_qty: Some(order.order_qty),
+                after_qty: None,
+            }),
+        }
+    }
+    book_diff
+}
+
+/// Order events that turn `old` into `new` when applied in this order with `OrderBook::apply`,
+/// tagged with their side since an `Order` doesn't carry it.
+///
+/// Deletes come first, then modifies, then adds in the feed order of `new`. A price change is
+/// expressed as delete and add, the order goes to the back of its new price level. Added orders
+/// are appended to their level, so `new` is reproduced as long as they are the newest ones there.
+pub fn derive_updates(old: &Level3Data, new: &Level3Data) -> Vec<(Side, Order)> {
+    let book_diff = diff(old, new);
+    let old_index = order_index(old);
+    let new_index = order_index(new);
+    let tagged = |side: Side, order: &Order, event: OrderEvent| {
+        let order = Order {
+            event: Some(event),
+            ..order.clone()
+        };
+        (side, order)
+    };
+
+    let mut deletes = Vec::new();
+    let mut modifies = Vec::new();
+    let mut adds = Vec::new();
+    for change in &book_diff.removed {
+        let (_, order) = old_index[change.order_id.as_str()];
+        deletes.push(tagged(change.side, order, OrderEvent::Delete));
+    }
+    for change in &book_diff.modified {
+        let (_, before) = old_index[change.order_id.as_str()];
+        let (_, after) = new_index[change.order_id.as_str()];
+        if before.limit_price == after.limit_price {
+            modifies.push(tagged(change.side, after, OrderEvent::Modify));
+        } else {
+            deletes.push(tagged(change.side, before, OrderEvent::Delete));
+            adds.push(tagged(change.side, after, OrderEvent::Add));
+        }
+    }
+    for change in &book_diff.added {
+        let (_, order) = new_index[change.order_id.as_str()];
+        adds.push(tagged(change.side, order, OrderEvent::Add));
+    }
+    // keep the adds in the feed order of `new` so same level arrivals stay in sequence
+    let feed_position: HashMap<&str, usize> = orders(new)
+        .enumerate()
+        .map(|(position, (_, order))| (order.order_id.as_str(), position))
+        .collect();
+    adds.sort_by_key(|(_, order)| feed_position[order.order_id.as_str()]);
+
+    deletes.into_iter().chain(modifies).chain(adds).collect()
+}
+
+fn orders(data: &Level3Data) -> impl Iterator<Item = (Side, &Order)> {
+    data.bids
+        .iter()
+        .map(|bid| (Side::Bid, bid))
+        .chain(data.asks.iter().map(|ask| (Side::Ask, ask)))
+}
+
+// order id index like the one kept by `OrderBook`
+fn order_index(data: &Level3Data) -> HashMap<&str, (Side, &Order)> {
+    orders(data)
+        .map(|(side, order)| (order.order_id.as_str(), (side, order)))
+        .collect()
+}
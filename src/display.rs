@@ -0,0 +1,160 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt::{Display, Write};
+
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::level2::to_level2;
+use crate::level3::{Level3Data, Side};
+use crate::symbol::precision_for;
+
+// price levels shown by `Display`
+const LADDER_LEVELS: usize = 10;
+
+// one ladder row: level, bid qty, bid price, ask price, ask qty (empty past the end of a side)
+struct LadderRow {
+    level: usize,
+    bid_qty: String,
+    bid_price: String,
+    ask_price: String,
+    ask_qty: String,
+}
+
+impl Level3Data {
+    /// Renders the top aggregated price levels as a two column bid/ask ladder, prices and
+    /// quantities are written with the symbol's precision (see `precision_for`). The header shows
+    /// the time of the newest order in UTC.
+    pub fn render_ladder(&self, levels: usize) -> String {
+        self.render_ladder_at(levels, UtcOffset::UTC)
+    }
+
+    /// The ladder of `render_ladder` with the time in the header at the given offset, e.g. the
+    /// trader's local time zone.
+    pub fn render_ladder_at(&self, levels: usize, offset: UtcOffset) -> String {
+        let mut ladder = self.ladder_header(offset);
+        for row in self.ladder_rows(levels) {
+            let line = format!(
+                "{:>4} {:>14} {:>10} | {:<10} {}",
+                row.level, row.bid_qty, row.bid_price, row.ask_price, row.ask_qty
+            );
+            writeln!(ladder, "{}", line.trim_end()).unwrap();
+        }
+        ladder
+    }
+
+    /// Writes the ladder of `render_ladder` with the bids in green and the asks in red, the best
+    /// level in bold. Enabled with the `termcolor` feature.
+    ///
+    /// Pass a writer with `ColorChoice::Never` to get the plain ladder, e.g. when stdout isn't a
+    /// terminal.
+    #[cfg(feature = "termcolor")]
+    pub fn write_ladder_colored(
+        &self,
+        levels: usize,
+        out: &mut impl termcolor::WriteColor,
+    ) -> std::io::Result<()> {
+        self.write_ladder_colored_at(levels, UtcOffset::UTC, out)
+    }
+
+    /// `write_ladder_colored` with the time in the header at the given offset.
+    #[cfg(feature = "termcolor")]
+    pub fn write_ladder_colored_at(
+        &self,
+        levels: usize,
+        offset: UtcOffset,
+        out: &mut impl termcolor::WriteColor,
+    ) -> std::io::Result<()> {
+        use termcolor::{Color, ColorSpec};
+
+        out.write_all(self.ladder_header(offset).as_bytes())?;
+        for row in self.ladder_rows(levels) {
+            let best = row.level == 1;
+            write!(out, "{:>4} ", row.level)?;
+            out.set_color(ColorSpec::new().set_fg(Some(Color::Green)).set_bold(best))?;
+            write!(out, "{:>14} {:>10}", row.bid_qty, row.bid_price)?;
+            out.reset()?;
+            write!(out, " |")?;
+            let asks = format!("{:<10} {}", row.ask_price, row.ask_qty);
+            let asks = asks.trim_end();
+            if !asks.is_empty() {
+                write!(out, " ")?;
+                out.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(best))?;
+                write!(out, "{}", asks)?;
+                out.reset()?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    // the time of the newest order is left out for an empty book
+    fn ladder_header(&self, offset: UtcOffset) -> String {
+        let mut header = String::new();
+        write!(header, "{} (checksum {}", self.symbol, self.checksum).unwrap();
+        let newest = self.iter().map(|(_, order)| order.timestamp).max();
+        if let Some(time) = newest.and_then(|newest| format_time(newest, offset)) {
+            write!(header, ", {}", time).unwrap();
+        }
+        writeln!(header, ")").unwrap();
+        writeln!(header, "{:>30} | ask        ask qty", "bid qty        bid").unwrap();
+        header
+    }
+
+    // prices and quantities are written with the symbol's precision
+    fn ladder_rows(&self, levels: usize) -> Vec<LadderRow> {
+        let precision = precision_for(&self.symbol).unwrap_or_default();
+        let qty = |qty: f64| format!("{:.*}", precision.qty_decimals as usize, qty);
+        let price = |price: f64| format!("{:.*}", precision.price_decimals as usize, price);
+        let bids = to_level2(self, Side::Bid);
+        let asks = to_level2(self, Side::Ask);
+        (0..levels.min(bids.len().max(asks.len())))
+            .map(|level| {
+                let (bid_qty, bid_price) = match bids.get(level) {
+                    Some((bid_price, bid_qty)) => (qty(*bid_qty), price(*bid_price)),
+                    None => (String::new(), String::new()),
+                };
+                let (ask_price, ask_qty) = match asks.get(level) {
+                    Some((ask_price, ask_qty)) => (price(*ask_price), qty(*ask_qty)),
+                    None => (String::new(), String::new()),
+                };
+                LadderRow {
+                    level: level + 1,
+                    bid_qty,
+                    bid_price,
+                    ask_price,
+                    ask_qty,
+                }
+            })
+            .collect()
+    }
+}
+
+// rfc3339 at the offset, `None` for times rfc3339 can't represent
+pub(crate) fn format_time(time: OffsetDateTime, offset: UtcOffset) -> Option<String> {
+    time.to_offset(offset).format(&Rfc3339).ok()
+}
+
+impl Display for Level3Data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_ladder(LADDER_LEVELS))
+    }
+}
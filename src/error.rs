@@ -0,0 +1,130 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use thiserror::Error;
+
+use crate::level3::Side;
+
+#[derive(Debug, Error)]
+pub enum Level3Error {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "bincode")]
+    #[error("bincode error: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[cfg(feature = "csv")]
+    #[error("csv error: {0}")]
+    Csv(#[from] csv::Error),
+    #[cfg(feature = "ws")]
+    #[error("websocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    #[cfg(feature = "ws")]
+    #[error("subscription rejected: {0}")]
+    SubscriptionRejected(String),
+    #[error("expected the message envelope to be an object")]
+    EnvelopeNotObject,
+    #[error("expected field '{field}' of type {expected} in the message envelope")]
+    InvalidEnvelope {
+        field: &'static str,
+        expected: &'static str,
+    },
+    #[error("snapshot contains no level3 data")]
+    EmptyData,
+    #[error("snapshot has more than {max_orders} orders")]
+    TooLarge { max_orders: usize },
+    #[error("snapshot for {found} can't be merged into {expected}")]
+    SymbolMismatch { expected: String, found: String },
+    #[error("symbol {symbol} not found in snapshot")]
+    UnknownSymbol { symbol: String },
+    #[error("snapshot contains {count} symbols, pick one")]
+    SymbolRequired { count: usize },
+    #[error("{field} {value} of order {order_id} does not fit the assumed precision")]
+    Precision {
+        order_id: String,
+        field: &'static str,
+        value: f64,
+    },
+    #[error("{field} {value} of order {order_id} is negative")]
+    Negative {
+        order_id: String,
+        field: &'static str,
+        value: f64,
+    },
+    #[error("scaling price or qty of order {order_id} overflows")]
+    Overflow { order_id: String },
+    #[error("order {order_id} is not in the book")]
+    UnknownOrder { order_id: String },
+    #[error("order {order_id} is already in the book")]
+    DuplicateOrder { order_id: String },
+    #[error(
+        "order id {order_id} appears twice: {first_side:?} index {first_index} and \
+         {second_side:?} index {second_index}"
+    )]
+    DuplicateId {
+        order_id: String,
+        first_side: Side,
+        first_index: usize,
+        second_side: Side,
+        second_index: usize,
+    },
+    #[error("{side:?} at index {index} has price {price} out of order after {previous_price}")]
+    Ordering {
+        side: Side,
+        index: usize,
+        price: f64,
+        previous_price: f64,
+    },
+    #[error("{side:?} has {levels} price levels, more than the requested depth {depth}")]
+    DepthExceeded {
+        side: Side,
+        levels: usize,
+        depth: usize,
+    },
+    #[error("book is crossed: best bid {bid} >= best ask {ask}")]
+    Crossed { bid: f64, ask: f64 },
+    #[error("order {order_id} has a timestamp {delta} in the future")]
+    FutureTimestamp {
+        order_id: String,
+        delta: time::Duration,
+    },
+    #[error("order {order_id} has a timestamp {age} in the past")]
+    StaleTimestamp {
+        order_id: String,
+        age: time::Duration,
+    },
+    #[error("checksum mismatch: expected {expected}, computed {computed}")]
+    ChecksumMismatch { expected: u32, computed: u32 },
+    #[error("checksum mismatch after update {index}: expected {expected}, computed {computed}")]
+    UpdateChecksumMismatch {
+        index: usize,
+        expected: u32,
+        computed: u32,
+    },
+}
+
+// boxed, the tungstenite error would make every `Result` of the crate large
+#[cfg(feature = "ws")]
+impl From<tokio_tungstenite::tungstenite::Error> for Level3Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Level3Error::WebSocket(Box::new(err))
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! CSV export of `Level3Data`, enabled with the `csv` feature, e.g. to load a capture into a
+//! spreadsheet or pandas.
+
+use std::io::Write;
+
+use time::format_description::well_known::Rfc3339;
+
+use crate::error::Level3Error;
+use crate::level3::{Level3Data, OrderEvent, Side};
+
+const HEADER: [&str; 6] = ["side", "order_id", "price", "qty", "timestamp", "event"];
+
+/// Writes a header row and one row per order, the asks first and then the bids like the checksum
+/// reads them.
+///
+/// Prices and quantities are written as the shortest decimal that reads back as the same float,
+/// timestamps as rfc3339 in UTC. The event is left empty for orders of a snapshot.
+pub fn to_csv<W: Write>(data: &Level3Data, writer: W) -> Result<(), Level3Error> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(HEADER)?;
+    for (side, order) in data {
+        let side = match side {
+            Side::Bid => "bid",
+            Side::Ask => "ask",
+        };
+        let event = match order.event {
+            None => "",
+            Some(OrderEvent::Add) => "add",
+            Some(OrderEvent::Modify) => "modify",
+            Some(OrderEvent::Delete) => "delete",
+            Some(OrderEvent::Snapshot) => "snapshot",
+        };
+        let timestamp = order
+            .timestamp
+            .format(&Rfc3339)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        csv_writer.write_record([
+            side,
+            &order.order_id,
+            &order.limit_price.to_string(),
+            &order.order_qty.to_string(),
+            &timestamp,
+            event,
+        ])?;
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::BTreeMap;
+
+use crate::checksum::ChecksumOptions;
+use crate::level3::{Level3Data, Order, Side};
+use crate::symbol::precision_for;
+
+/// Aggregate of the orders resting at one price.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub total_qty: f64,
+    pub order_count: usize,
+}
+
+/// Collapses the orders of one side into (price, total qty) levels, best price first.
+///
+/// Prices and quantities are summed as integers scaled by the symbol's precision (see
+/// `precision_for`), so the totals don't pick up float drift.
+pub fn to_level2(data: &Level3Data, side: Side) -> Vec<(f64, f64)> {
+    let precision = precision_for(&data.symbol).unwrap_or_default();
+    let price_factor = precision.price_factor();
+    let qty_factor = precision.qty_factor();
+
+    let mut levels: BTreeMap<i64, i64> = BTreeMap::new();
+    for order in data.orders(side) {
+        let price_i = (order.limit_price * price_factor).round() as i64;
+        let qty_i = (order.order_qty * qty_factor).round() as i64;
+        *levels.entry(price_i).or_default() += qty_i;
+    }
+
+    let levels = levels
+        .into_iter()
+        .map(|(price_i, qty_i)| (price_i as f64 / price_factor, qty_i as f64 / qty_factor));
+    match side {
+        Side::Bid => levels.rev().collect(),
+        Side::Ask => levels.collect(),
+    }
+}
+
+/// Cumulative (price, qty) depth of one side walking from the best price outward, the data of a
+/// depth chart.
+///
+/// Built on `to_level2`, the running total is kept as a scaled integer like the level totals.
+pub fn depth_profile(data: &Level3Data, side: Side) -> Vec<(f64, f64)> {
+    let qty_factor = precision_for(&data.symbol).unwrap_or_default().qty_factor();
+    let mut cumulative_i: i64 = 0;
+    to_level2(data, side)
+        .into_iter()
+        .map(|(price, qty)| {
+            cumulative_i += (qty * qty_factor).round() as i64;
+            (price, cumulative_i as f64 / qty_factor)
+        })
+        .collect()
+}
+
+/// Lazily groups consecutive orders with the same price into levels, in feed order.
+///
+/// Unlike `to_level2` nothing is collected up front, which suits taking only the top few levels.
+/// The orders are expected to be sorted like the feed sends them, the same price showing up again
+/// after another price starts a new level. Prices belong to one level like the checksum groups
+/// them with the symbol's options (see `ChecksumOptions::same_price`), the level has the price of
+/// its first order. Quantities are summed as integers scaled by the symbol's precision.
+pub fn price_levels(data: &Level3Data, side: Side) -> impl Iterator<Item = PriceLevel> + '_ {
+    PriceLevels {
+        orders: data.orders(side),
+        opts: ChecksumOptions::for_symbol(&data.symbol),
+    }
+}
+
+struct PriceLevels<'a> {
+    orders: &'a [Order],
+    opts: ChecksumOptions,
+}
+
+impl Iterator for PriceLevels<'_> {
+    type Item = PriceLevel;
+
+    fn next(&mut self) -> Option<PriceLevel> {
+        let price = self.orders.first()?.limit_price;
+        // a NaN price doesn't equal itself, it still forms a level of its own
+        let order_count = self
+            .orders
+            .iter()
+            .take_while(|order| self.opts.same_price(order.limit_price, price))
+            .count()
+            .max(1);
+        let (level, rest) = self.orders.split_at(order_count);
+        self.orders = rest;
+
+        let qty_factor = self.opts.precision.qty_factor();
+        let qty_i: i64 = level
+            .iter()
+            .map(|order| (order.order_qty * qty_factor).round() as i64)
+            .sum();
+        Some(PriceLevel {
+            price,
+            total_qty: qty_i as f64 / qty_factor,
+            order_count,
+        })
+    }
+}
@@ -0,0 +1,522 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use serde::de::{self, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_this_or_that::as_f64;
+use serde_with::skip_serializing_none;
+
+use crate::checksum::{ChecksumOptions, Precision};
+use crate::error::Level3Error;
+
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Level3Data {
+    pub symbol: String,
+    pub bids: Vec<Order>,
+    pub asks: Vec<Order>,
+    pub checksum: u32,
+}
+
+impl Level3Data {
+    pub fn orders(&self, side: Side) -> &[Order] {
+        match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        }
+    }
+
+    /// Highest bid, the bids are expected in checksum order (descending price). `None` if there
+    /// are no bids.
+    pub fn best_bid(&self) -> Option<&Order> {
+        best_of(&self.bids)
+    }
+
+    /// Lowest ask, the asks are expected in checksum order (ascending price). `None` if there are
+    /// no asks.
+    pub fn best_ask(&self) -> Option<&Order> {
+        best_of(&self.asks)
+    }
+
+    /// All orders tagged with their side, the asks first and then the bids like the checksum
+    /// reads them. `for (side, order) in &level3_data` does the same.
+    pub fn iter(&self) -> OrderIter<'_> {
+        OrderIter {
+            asks: self.asks.iter(),
+            bids: self.bids.iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Level3Data {
+    type Item = (Side, &'a Order);
+    type IntoIter = OrderIter<'a>;
+
+    fn into_iter(self) -> OrderIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the orders of both sides, see `Level3Data::iter`.
+#[derive(Debug, Clone)]
+pub struct OrderIter<'a> {
+    asks: std::slice::Iter<'a, Order>,
+    bids: std::slice::Iter<'a, Order>,
+}
+
+impl<'a> Iterator for OrderIter<'a> {
+    type Item = (Side, &'a Order);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.asks.next() {
+            Some(ask) => Some((Side::Ask, ask)),
+            None => self.bids.next().map(|bid| (Side::Bid, bid)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.asks.len() + self.bids.len();
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for OrderIter<'_> {}
+
+// picks the oldest order (then lowest order_id) among the orders at the first price level
+pub(crate) fn best_of(orders: &[Order]) -> Option<&Order> {
+    let best_price = orders.first()?.limit_price;
+    orders
+        .iter()
+        .take_while(|order| order.limit_price == best_price)
+        .min_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.order_id.cmp(&b.order_id))
+        })
+}
+
+#[skip_serializing_none]
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Order {
+    pub event: Option<OrderEvent>,
+    pub order_id: String,
+    #[serde(deserialize_with = "as_f64")]
+    pub limit_price: f64,
+    #[serde(deserialize_with = "as_f64")]
+    pub order_qty: f64,
+    /// Written as rfc3339, read from rfc3339 or integer unix nanoseconds.
+    #[serde(
+        serialize_with = "time::serde::rfc3339::serialize",
+        deserialize_with = "timestamp"
+    )]
+    pub timestamp: time::OffsetDateTime,
+}
+
+impl Order {
+    /// The `Debug` line of the order with qty and price written at the given precision, e.g. the
+    /// symbol's `precision_for`. `Debug` uses the default precision of BTC/USD.
+    pub fn format_with(&self, precision: Precision) -> String {
+        let timestamp = format!(
+            "{:.6}",
+            self.timestamp.unix_timestamp_nanos() as f64 / 1.0e9
+        );
+        self.format_line(precision, &timestamp)
+    }
+
+    /// The line of `format_with` with the timestamp written as rfc3339 at the given offset
+    /// instead of unix seconds, e.g. `2024-06-17T14:00:00+02:00`.
+    pub fn format_at(&self, precision: Precision, offset: time::UtcOffset) -> String {
+        match crate::display::format_time(self.timestamp, offset) {
+            Some(timestamp) => self.format_line(precision, &timestamp),
+            None => self.format_with(precision),
+        }
+    }
+
+    fn format_line(&self, precision: Precision, timestamp: &str) -> String {
+        let qty_decimals = precision.qty_decimals as usize;
+        let price_decimals = precision.price_decimals as usize;
+        format!(
+            "{}: {:qty_width$.qty_decimals$} @ {:<price_width$.price_decimals$} {}",
+            self.order_id,
+            self.order_qty,
+            self.limit_price,
+            timestamp,
+            // room for 3 integer digits of the qty and 5 of the price
+            qty_width = qty_decimals + 4,
+            price_width = price_decimals + 6,
+        )
+    }
+}
+
+impl Debug for Order {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format_with(Precision::default()))
+    }
+}
+#[skip_serializing_none]
+#[derive(PartialEq, Debug, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum OrderEvent {
+    #[serde(rename = "add")]
+    Add,
+    #[serde(rename = "modify")]
+    Modify,
+    #[serde(rename = "delete")]
+    Delete,
+    /// Marks the orders of a snapshot in some level3 variants, inserted like `Add`.
+    #[serde(rename = "snapshot")]
+    Snapshot,
+}
+
+// accepts the string names as well as the integer codes 0/1/2 used by some transports, the
+// snapshot marker is only sent by name
+impl<'de> Deserialize<'de> for OrderEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(OrderEventVisitor)
+    }
+}
+
+struct OrderEventVisitor;
+
+impl<'de> Visitor<'de> for OrderEventVisitor {
+    type Value = OrderEvent;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an order event name or code")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        match value {
+            "add" => Ok(OrderEvent::Add),
+            "modify" => Ok(OrderEvent::Modify),
+            "delete" => Ok(OrderEvent::Delete),
+            "snapshot" => Ok(OrderEvent::Snapshot),
+            _ => Err(E::unknown_variant(
+                value,
+                &["add", "modify", "delete", "snapshot"],
+            )),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        match value {
+            0 => Ok(OrderEvent::Add),
+            1 => Ok(OrderEvent::Modify),
+            2 => Ok(OrderEvent::Delete),
+            _ => Err(E::invalid_value(Unexpected::Unsigned(value), &self)),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        match u64::try_from(value) {
+            Ok(value) => self.visit_u64(value),
+            Err(_) => Err(E::invalid_value(Unexpected::Signed(value), &self)),
+        }
+    }
+}
+
+// accepts rfc3339 strings as well as the integer unix nanoseconds some feeds send
+pub(crate) fn timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<time::OffsetDateTime, D::Error> {
+    deserializer.deserialize_any(TimestampVisitor)
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = time::OffsetDateTime;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an rfc3339 timestamp or unix nanoseconds")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+            .map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        self.visit_i128(value.into())
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        self.visit_i128(value.into())
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Self::Value, E> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(value).map_err(E::custom)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+/// Checksum order of two orders on the given side: best price first, then the oldest timestamp,
+/// then the lower order_id.
+///
+/// Only the price comparison depends on the side, bids sort by descending and asks by ascending
+/// price. Timestamps and order ids are ascending on both sides, which is how the feed queues the
+/// orders of a price level.
+pub fn cmp_orders(side: Side, a: &Order, b: &Order) -> Ordering {
+    let by_price = match side {
+        Side::Bid => b.limit_price.total_cmp(&a.limit_price),
+        Side::Ask => a.limit_price.total_cmp(&b.limit_price),
+    };
+    by_price
+        .then_with(|| a.timestamp.cmp(&b.timestamp))
+        .then_with(|| a.order_id.cmp(&b.order_id))
+}
+
+/// Sorts the orders of one side with `cmp_orders`, a no-op for data sorted like the feed.
+pub fn sort_orders(side: Side, orders: &mut [Order]) {
+    orders.sort_by(|a, b| cmp_orders(side, a, b));
+}
+
+/// Parses a level3 snapshot message and returns the `Level3Data` entries of its `data` array,
+/// one per symbol.
+pub fn parse_snapshot(line_str: &str) -> Result<Vec<Level3Data>, Level3Error> {
+    snapshot_data(serde_json::from_str(line_str)?)
+}
+
+/// Parses a level3 snapshot message from a reader, e.g. a socket or stdin.
+pub fn from_reader<R: Read>(reader: R) -> Result<Vec<Level3Data>, Level3Error> {
+    snapshot_data(serde_json::from_reader(reader)?)
+}
+
+/// Takes the `data` array out of an already parsed message without cloning it, the rest of the
+/// message is dropped.
+///
+/// A missing `data` field or one that isn't an array is reported as
+/// `Level3Error::InvalidEnvelope`, an empty array as `Level3Error::EmptyData`.
+pub fn extract_data(mut value: serde_json::Value) -> Result<Vec<Level3Data>, Level3Error> {
+    let level3_data = match value.get_mut("data").map(serde_json::Value::take) {
+        Some(level3_data) if level3_data.is_array() => level3_data,
+        _ => {
+            return Err(Level3Error::InvalidEnvelope {
+                field: "data",
+                expected: "array",
+            })
+        }
+    };
+    snapshot_data(DataEnvelope {
+        level3_data: serde_json::from_value(level3_data)?,
+    })
+}
+
+/// Parses only the entry of `symbol` out of a snapshot message with several symbols, `None` if
+/// the symbol isn't in it.
+///
+/// The other entries are only scanned for their symbol, their orders are not built.
+pub fn parse_symbol(line_str: &str, symbol: &str) -> Result<Option<Level3Data>, Level3Error> {
+    #[derive(Deserialize)]
+    struct SymbolOnly<'a> {
+        #[serde(borrow)]
+        symbol: std::borrow::Cow<'a, str>,
+    }
+
+    let envelope: DataEnvelope<&serde_json::value::RawValue> = serde_json::from_str(line_str)?;
+    for raw in snapshot_data(envelope)? {
+        let entry: SymbolOnly = serde_json::from_str(raw.get())?;
+        if entry.symbol == symbol {
+            return Ok(Some(serde_json::from_str(raw.get())?));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads and parses a level3 snapshot message from a JSON file.
+pub fn from_file(path: impl AsRef<Path>) -> Result<Vec<Level3Data>, Level3Error> {
+    from_reader(BufReader::new(File::open(path)?))
+}
+
+/// Reads and parses a gzip compressed level3 snapshot message.
+#[cfg(feature = "gzip")]
+pub fn from_gzip_file(path: impl AsRef<Path>) -> Result<Vec<Level3Data>, Level3Error> {
+    from_reader(flate2::read::GzDecoder::new(BufReader::new(File::open(
+        path,
+    )?)))
+}
+
+// only the data array of the message is deserialized, the other envelope fields are skipped
+#[derive(Deserialize)]
+pub(crate) struct DataEnvelope<T> {
+    #[serde(rename = "data")]
+    pub(crate) level3_data: Vec<T>,
+}
+
+pub(crate) fn snapshot_data<T>(envelope: DataEnvelope<T>) -> Result<Vec<T>, Level3Error> {
+    if envelope.level3_data.is_empty() {
+        return Err(Level3Error::EmptyData);
+    }
+    Ok(envelope.level3_data)
+}
+
+/// All orders of both sides tagged with their side, oldest first (ties ordered by order_id).
+pub fn events_by_time(data: &Level3Data) -> Vec<(Side, &Order)> {
+    let mut events: Vec<(Side, &Order)> = data
+        .bids
+        .iter()
+        .map(|bid| (Side::Bid, bid))
+        .chain(data.asks.iter().map(|ask| (Side::Ask, ask)))
+        .collect();
+    events.sort_by(|(_, a), (_, b)| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.order_id.cmp(&b.order_id))
+    });
+    events
+}
+
+pub fn to_json(data: &Level3Data) -> Result<String, Level3Error> {
+    Ok(serde_json::to_string(data)?)
+}
+
+pub fn by_symbol<'a>(level3_data: &'a [Level3Data], symbol: &str) -> Option<&'a Level3Data> {
+    level3_data.iter().find(|data| data.symbol == symbol)
+}
+
+/// The data of `symbol`, without a symbol the snapshot must carry exactly one.
+///
+/// An unknown symbol is `Level3Error::UnknownSymbol`, a missing one with several in the snapshot
+/// `Level3Error::SymbolRequired`.
+pub fn select_symbol<'a>(
+    level3_data: &'a [Level3Data],
+    symbol: Option<&str>,
+) -> Result<&'a Level3Data, Level3Error> {
+    match symbol {
+        Some(symbol) => by_symbol(level3_data, symbol).ok_or_else(|| Level3Error::UnknownSymbol {
+            symbol: symbol.to_string(),
+        }),
+        None if level3_data.len() == 1 => Ok(&level3_data[0]),
+        None => Err(Level3Error::SymbolRequired {
+            count: level3_data.len(),
+        }),
+    }
+}
+
+/// Keeps only the top `depth` price levels of each side, always whole levels with all their
+/// orders, so the checksum over up to `depth` levels is unchanged.
+///
+/// Levels are told apart like the checksum does with the symbol's options, see
+/// `ChecksumOptions::same_price`. With `ChecksumOptions::level3_bug` the checksum reaches
+/// `last_level(Side::Ask)` ask levels, keep at least that many.
+pub fn truncate(data: &mut Level3Data, depth: usize) {
+    let opts = ChecksumOptions::for_symbol(&data.symbol);
+    for orders in [&mut data.bids, &mut data.asks] {
+        let mut price_level_count = 0;
+        let mut curr_price: Option<f64> = None;
+        let keep = orders
+            .iter()
+            .take_while(|order| {
+                if !curr_price.is_some_and(|price| opts.same_price(order.limit_price, price)) {
+                    curr_price = Some(order.limit_price);
+                    price_level_count += 1;
+                }
+                price_level_count <= depth
+            })
+            .count();
+        orders.truncate(keep);
+    }
+}
+
+/// Sorts both sides with `cmp_orders` and drops orders that are exact duplicates of another.
+///
+/// After this the checksum doesn't depend on the order the feed listed the orders in.
+pub fn normalize(data: &mut Level3Data) {
+    for (side, orders) in [(Side::Bid, &mut data.bids), (Side::Ask, &mut data.asks)] {
+        sort_orders(side, orders);
+        orders.dedup();
+    }
+}
+
+/// Reassembles a book that was delivered as several partial snapshots of the same symbol.
+///
+/// The orders are concatenated and sorted with `cmp_orders`, an order id that appears more than
+/// once keeps its first occurrence. The checksum is the one of the last snapshot, which covers the
+/// whole book. Snapshots of different symbols are a `Level3Error::SymbolMismatch`, an empty slice
+/// is `Level3Error::EmptyData`.
+pub fn merge(snapshots: &[Level3Data]) -> Result<Level3Data, Level3Error> {
+    let (first, last) = match (snapshots.first(), snapshots.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(Level3Error::EmptyData),
+    };
+    let mut merged = Level3Data {
+        symbol: first.symbol.clone(),
+        bids: Vec::new(),
+        asks: Vec::new(),
+        checksum: last.checksum,
+    };
+    let mut seen = HashSet::new();
+    for snapshot in snapshots {
+        if snapshot.symbol != merged.symbol {
+            return Err(Level3Error::SymbolMismatch {
+                expected: merged.symbol,
+                found: snapshot.symbol.clone(),
+            });
+        }
+        for (side, order) in snapshot {
+            if seen.insert(order.order_id.as_str()) {
+                match side {
+                    Side::Bid => merged.bids.push(order.clone()),
+                    Side::Ask => merged.asks.push(order.clone()),
+                }
+            }
+        }
+    }
+    sort_orders(Side::Bid, &mut merged.bids);
+    sort_orders(Side::Ask, &mut merged.asks);
+    Ok(merged)
+}
+
+/// Removes the orders that are more than `max_age` older than the newest order of the book and
+/// returns how many were removed.
+///
+/// The embedded `checksum` no longer matches once orders were removed, call `refresh_checksum`
+/// if the data is passed on.
+pub fn filter_stale(data: &mut Level3Data, max_age: time::Duration) -> usize {
+    let newest = match data
+        .bids
+        .iter()
+        .chain(data.asks.iter())
+        .map(|order| order.timestamp)
+        .max()
+    {
+        Some(newest) => newest,
+        None => return 0,
+    };
+    let order_count = data.bids.len() + data.asks.len();
+    for orders in [&mut data.bids, &mut data.asks] {
+        orders.retain(|order| newest - order.timestamp <= max_age);
+    }
+    order_count - data.bids.len() - data.asks.len()
+}
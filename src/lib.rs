@@ -0,0 +1,121 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Without the default `std` feature only the `core_checksum` module is available, the crate
+//! is `no_std` then.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod analytics;
+#[cfg(feature = "bincode")]
+pub mod binary;
+#[cfg(feature = "std")]
+pub mod book;
+#[cfg(feature = "test-util")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod checksum;
+pub mod core_checksum;
+#[cfg(feature = "decimal")]
+pub mod decimal;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+mod display;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "csv")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod level2;
+#[cfg(feature = "std")]
+pub mod level3;
+#[cfg(feature = "std")]
+pub mod limit;
+#[cfg(feature = "std")]
+pub mod message;
+#[cfg(feature = "std")]
+mod seed;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod symbol;
+#[cfg(feature = "std")]
+pub mod validate;
+#[cfg(feature = "ws")]
+pub mod ws;
+
+#[cfg(feature = "std")]
+pub use analytics::{
+    imbalance, mid_price, notional, order_rank, orders_at_price, spread, stats, top_orders,
+    vwap_for_size, BookStats,
+};
+#[cfg(feature = "bincode")]
+pub use binary::{from_bytes, to_bytes};
+#[cfg(feature = "std")]
+pub use book::{apply_updates, book_checksum, OrderBook};
+#[cfg(feature = "test-util")]
+pub use builder::OrderBuilder;
+#[cfg(feature = "std")]
+pub use checksum::{
+    checksum_diff, checksum_digits, checksum_digits_with, checksum_input_string,
+    checksum_input_with_warnings, checksum_matches, checksum_near, checksum_of, checksum_orders,
+    checksum_side, checksum_with_warnings, compute_checksum, explain_checksum,
+    order_checksum_contribution, refresh_checksum, try_checksum_diff, try_checksum_input_string,
+    try_checksum_of, try_checksum_side, try_checksum_with_levels, try_compute_checksum,
+    try_compute_checksum_with, try_explain_checksum, verify_against, verify_checksum,
+    ChecksumBuilder, ChecksumComparison, ChecksumEntry, ChecksumOptions, ChecksumVersion,
+    Checksummer, Crc32, PrecisionWarning, RoundingMode,
+};
+pub use core_checksum::{raw_checksum, scaled_digits, Precision, RawOrder};
+#[cfg(feature = "std")]
+pub use diff::{derive_updates, diff, BookDiff, OrderChange};
+#[cfg(feature = "std")]
+pub use error::Level3Error;
+#[cfg(feature = "csv")]
+pub use export::to_csv;
+#[cfg(feature = "std")]
+pub use level2::{depth_profile, price_levels, to_level2, PriceLevel};
+#[cfg(feature = "gzip")]
+pub use level3::from_gzip_file;
+#[cfg(feature = "std")]
+pub use level3::{
+    by_symbol, cmp_orders, events_by_time, extract_data, filter_stale, from_file, from_reader,
+    merge, normalize, parse_snapshot, parse_symbol, select_symbol, sort_orders, to_json, truncate,
+    Level3Data, Order, OrderEvent, OrderIter, Side,
+};
+#[cfg(feature = "std")]
+pub use limit::{from_reader_with, parse_snapshot_with, ParseOptions};
+#[cfg(feature = "std")]
+pub use message::{
+    parse, parse_message, parse_message_with, parse_ndjson, parse_ndjson_with, validate_envelope,
+    Message, ParsedMessage,
+};
+#[cfg(feature = "std")]
+pub use stream::{stream_checksums, verify_stream, StreamChecksum};
+#[cfg(feature = "std")]
+pub use symbol::{precision_for, register_symbol, SymbolSpec};
+#[cfg(feature = "std")]
+pub use validate::{
+    check_timestamps, check_timestamps_at, check_unique_ids, health_check, health_check_with,
+    inferred_depth, is_crossed, is_locked, load_and_verify, validate, validate_depth,
+    validate_ordering, verify_snapshot, HealthReport, VerifyReport, SNAPSHOT_DEPTHS,
+};
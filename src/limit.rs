@@ -0,0 +1,81 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::io::Read;
+
+use serde::de;
+use serde::Deserializer;
+
+use crate::error::Level3Error;
+use crate::level3::{from_reader, parse_snapshot, snapshot_data, DataEnvelope, Level3Data};
+use crate::seed::{Budget, EnvelopeVisitor, Limits};
+
+/// Limits applied while a message is parsed, unlimited by default.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Orders of all symbols and both sides the message may hold, more are reported as
+    /// `Level3Error::TooLarge` as soon as they are read, before the book grows any further.
+    pub max_orders: Option<usize>,
+}
+
+/// Like `parse_snapshot` within the limits of `opts`, e.g. for a feed that isn't trusted.
+pub fn parse_snapshot_with(
+    line_str: &str,
+    opts: &ParseOptions,
+) -> Result<Vec<Level3Data>, Level3Error> {
+    match opts.max_orders {
+        Some(max_orders) => limited(
+            &mut serde_json::Deserializer::from_str(line_str),
+            max_orders,
+        ),
+        None => parse_snapshot(line_str),
+    }
+}
+
+/// Like `from_reader` within the limits of `opts`.
+pub fn from_reader_with<R: Read>(
+    reader: R,
+    opts: &ParseOptions,
+) -> Result<Vec<Level3Data>, Level3Error> {
+    match opts.max_orders {
+        Some(max_orders) => limited(
+            &mut serde_json::Deserializer::from_reader(reader),
+            max_orders,
+        ),
+        None => from_reader(reader),
+    }
+}
+
+// the order count is shared by all sides of all symbols
+fn limited<'de, R: serde_json::de::Read<'de>>(
+    deserializer: &mut serde_json::Deserializer<R>,
+    max_orders: usize,
+) -> Result<Vec<Level3Data>, Level3Error> {
+    let budget = Budget::new(max_orders);
+    let limits = Limits {
+        budget: Some(&budget),
+        ..Limits::default()
+    };
+    let level3_data = budget
+        .checked(deserializer.deserialize_map(EnvelopeVisitor { limits }))?
+        .ok_or_else(|| <serde_json::Error as de::Error>::missing_field("data"))?;
+    deserializer.end()?;
+    snapshot_data(DataEnvelope { level3_data })
+}
@@ -18,188 +18,361 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::fmt::Debug;
-
-use serde::{Deserialize, Serialize};
-use serde_this_or_that::as_f64;
-use serde_with::skip_serializing_none;
-#[skip_serializing_none]
-#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct Level3Data {
-    pub symbol: String,
-    pub bids: Vec<Order>,
-    pub asks: Vec<Order>,
-    pub checksum: u32,
-}
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Read};
+use std::path::{Path, PathBuf};
 
-#[skip_serializing_none]
-#[derive(PartialEq, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct Order {
-    pub event: Option<OrderEvent>,
-    pub order_id: String,
-    #[serde(deserialize_with = "as_f64")]
-    pub limit_price: f64,
-    #[serde(deserialize_with = "as_f64")]
-    pub order_qty: f64,
-    #[serde(with = "time::serde::rfc3339")]
-    pub timestamp: time::OffsetDateTime,
-}
+use clap::{Parser, Subcommand};
+use level3bug::{
+    checksum_input_with_warnings, diff, health_check_with, parse_message, parse_snapshot,
+    select_symbol, try_explain_checksum, verify_snapshot, ChecksumOptions, Level3Error, Message,
+    OrderBook,
+};
+use time::UtcOffset;
+use tracing::{debug, info};
+use tracing_subscriber::EnvFilter;
 
-impl Debug for Order {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!(
-            "{}: {:12.8} @ {:<7.1} {:.6}",
-            self.order_id,
-            self.order_qty,
-            self.limit_price,
-            self.timestamp.unix_timestamp_nanos() as f64 / 1.0e9
-        ))
-    }
-}
-#[skip_serializing_none]
-#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub enum OrderEvent {
-    #[serde(rename = "add")]
-    Add,
-    #[serde(rename = "modify")]
-    Modify,
-    #[serde(rename = "delete")]
-    Delete,
+#[derive(Parser)]
+#[command(about = "Verifies the checksum of a Kraken level3 snapshot")]
+struct Args {
+    /// Snapshot file to parse, `-` reads stdin. Without a file piped stdin is read, otherwise
+    /// level3-bug.json (or level3-doc.json with --reference)
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Treat the snapshot as the api docs reference, i.e. without Kraken's ask level quirk
+    #[arg(long)]
+    reference: bool,
+    /// Symbol to verify if the snapshot contains more than one
+    #[arg(long)]
+    symbol: Option<String>,
+    /// Scale the checksum digits from the float values, values that don't fit the precision are
+    /// rounded and reported as warnings. `--strict-float` is a deprecated alias
+    #[arg(long, alias = "strict-float")]
+    round_float: bool,
+    /// Print every price/qty pair that enters the checksum with the CRC input up to it
+    #[arg(long)]
+    explain: bool,
+    /// Print the outcome of all snapshot checks, see `health_check`
+    #[arg(long)]
+    health: bool,
+    /// Offset of the time in the ladder header, e.g. `+02:00` or `-05:00`
+    #[arg(long, value_parser = parse_utc_offset, default_value = "+00:00")]
+    utc_offset: UtcOffset,
+    /// Keep running and verify the file again whenever it changes on disk, see `load_and_verify`
+    #[cfg(feature = "watch")]
+    #[arg(long)]
+    watch: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-const PRICE_PRECISION_FACTOR: f64 = 10i64.pow(1) as f64;
-const QTY_PRECISION_FACTOR: f64 = 10i64.pow(8) as f64;
+#[derive(Subcommand)]
+enum Command {
+    /// Applies the updates of an ndjson capture to its snapshot and stops at the first message
+    /// whose checksum doesn't match, exits with 1 in that case
+    Replay {
+        /// Capture with one message per line, starting with a snapshot
+        file: PathBuf,
+        /// Skip ask levels 10 and 11 like Kraken's checksum of level3-bug.json, live captures
+        /// are checked with the symbol's options
+        #[arg(long)]
+        ask_level_quirk: bool,
+    },
+}
 
 pub fn main() {
     // to parse buggy json run: cargo run
-    // to parse api docs reference json run: cargo run -- ref
+    // to parse api docs reference json run: cargo run -- --reference
 
-    let use_reference = std::env::args().nth(1).map_or(false, |arg| arg == "ref");
+    // RUST_LOG=trace logs every order that is included in or skipped from the checksum
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
 
-    let line_str = if use_reference {
-        // read the JSON string from file "level3-doc.json" (example from kraken api website)
-        std::fs::read_to_string("level3-doc.json").unwrap()
+    let args = Args::parse();
+    let use_reference = args.reference;
+
+    // the buggy snapshot only matches with Kraken's ask level quirk applied
+    let mut opts = if use_reference {
+        ChecksumOptions::default()
     } else {
-        // read the JSON string from file "level3-bug.json"
-        std::fs::read_to_string("level3-bug.json").unwrap()
+        ChecksumOptions::level3_bug()
     };
-    let snapshot: serde_json::Value = serde_json::from_str(&line_str).unwrap();
+    opts.strict_precision = !args.round_float;
 
-    let data_array = snapshot["data"].clone();
-    let level3_data: Vec<Level3Data> = serde_json::from_value(data_array).unwrap();
-    assert!(level3_data.len() == 1);
-    let level3_data = &level3_data[0];
+    if let Some(Command::Replay {
+        file,
+        ask_level_quirk,
+    }) = &args.command
+    {
+        replay(file, args.symbol.as_deref(), *ask_level_quirk);
+        return;
+    }
 
-    let mut crc_str = String::new();
+    let path = args.file.unwrap_or_else(|| {
+        if !std::io::stdin().is_terminal() {
+            // read the JSON string from a pipe, e.g. cat snapshot.json | level3bug
+            PathBuf::from("-")
+        } else if use_reference {
+            // read the JSON string from file "level3-doc.json" (example from kraken api website)
+            PathBuf::from("level3-doc.json")
+        } else {
+            // read the JSON string from file "level3-bug.json"
+            PathBuf::from("level3-bug.json")
+        }
+    });
+    let line_str = if path.as_os_str() == "-" {
+        let mut line_str = String::new();
+        std::io::stdin()
+            .read_to_string(&mut line_str)
+            .unwrap_or_else(|err| fail(format!("stdin: {}", err)));
+        line_str
+    } else {
+        std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| fail(format!("{}: {}", path.display(), err)))
+    };
+    let level3_data = parse_snapshot(&line_str).unwrap_or_else(|err| fail(err));
+    let level3_data =
+        select_symbol(&level3_data, args.symbol.as_deref()).unwrap_or_else(|err| fail(err));
 
-    println!("===============================================================================");
-    {
-        let asks_it = level3_data.asks.iter();
-        let mut curr_price: f64 = 0.0;
-        let mut price_level_count = 0;
-        for ask in asks_it {
-            if ask.limit_price != curr_price {
-                curr_price = ask.limit_price;
-                price_level_count += 1;
-                if price_level_count > 13 {
-                    break;
-                }
-            }
+    print_ladder(level3_data, args.utc_offset);
+    if args.round_float {
+        let (_, warnings) = checksum_input_with_warnings(level3_data, &opts);
+        for warning in &warnings {
+            println!("WARNING: {}", warning);
+        }
+    }
+    let report = verify_snapshot(&line_str, Some(&level3_data.symbol), &opts)
+        .unwrap_or_else(|err| fail(err));
+    if args.explain {
+        print_explain(level3_data, &opts);
+    }
+    if args.health {
+        print!("{}", health_check_with(level3_data, &opts));
+    }
+    debug!(crc_str = %report.crc_input, "checksum input");
+    info!(
+        expected = report.expected,
+        computed = report.computed,
+        "checksum"
+    );
+
+    if !report.matches {
+        println!("ERROR: Checksum mismatch!");
+    } else {
+        println!("Checksum OK!");
+    }
+
+    #[cfg(feature = "watch")]
+    if args.watch {
+        let last_good = report.matches.then_some(report.crc_input);
+        watch(&path, &level3_data.symbol, &opts, last_good);
+    }
+}
 
-            let price_f = ask.limit_price * PRICE_PRECISION_FACTOR;
-            let price_i = price_f.round() as i64;
-            let price_if = price_i as f64;
-            assert!((price_f - price_if).abs() < 1e-3);
-            let price_s = price_i.to_string();
-
-            let qty_f = ask.order_qty * QTY_PRECISION_FACTOR;
-            let qty_i = qty_f.round() as i64;
-            let qty_if = qty_i as f64;
-            assert!((qty_f - qty_if).abs() < 1e-3);
-            let qty_s = qty_i.to_string();
-
-            if use_reference {
-                if price_level_count < 11 {
-                    crc_str.push_str(&price_s);
-                    crc_str.push_str(&qty_s);
+// reports an error the way every failure of the binary is reported
+fn fail(message: impl Display) -> ! {
+    println!("ERROR: {}", message);
+    std::process::exit(1);
+}
+
+// re-verifies the file on every change, printing only the outcome and on a mismatch the first
+// level that differs from the last matching version
+#[cfg(feature = "watch")]
+fn watch(path: &Path, symbol: &str, opts: &ChecksumOptions, mut last_good: Option<String>) {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use level3bug::{from_file, load_and_verify, try_checksum_diff};
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    if path.as_os_str() == "-" {
+        println!("ERROR: --watch needs a file");
+        std::process::exit(1);
+    }
+    // capture tools often replace the file, so the directory is watched rather than the file
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).expect("failed to create file watcher");
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .expect("failed to watch the snapshot directory");
+    println!("Watching {} for changes", path.display());
+    while let Ok(event) = rx.recv() {
+        let changed = match event {
+            Ok(event) => {
+                matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|changed| changed.file_name() == path.file_name())
+            }
+            Err(err) => {
+                println!("ERROR: {}", err);
+                false
+            }
+        };
+        if !changed {
+            continue;
+        }
+        // a single write shows up as several events, let them settle
+        while rx.recv_timeout(Duration::from_millis(100)).is_ok() {}
+        match load_and_verify(path, Some(symbol), opts) {
+            Ok(report) if report.matches => {
+                println!("Checksum OK!");
+                last_good = Some(report.crc_input);
+            }
+            Ok(report) => {
+                println!(
+                    "ERROR: checksum mismatch: expected {}, computed {}",
+                    report.expected, report.computed
+                );
+                let Some(last_good) = &last_good else {
+                    continue;
+                };
+                let level3_data = from_file(path).ok().and_then(|level3_data| {
+                    level3_data.into_iter().find(|data| data.symbol == symbol)
+                });
+                if let Some(Ok(Some((side, level)))) =
+                    level3_data.map(|level3_data| try_checksum_diff(&level3_data, opts, last_good))
+                {
                     println!(
-                        "Ask level {:2}: {:?} | price_s: {} qty_s: {:>11}",
-                        price_level_count, ask, price_s, qty_s
+                        "first difference to the last matching version: {:?} level {}",
+                        side, level
                     );
-                } else {
-                    println!("(ignore Ask level {:2}: {:?})", price_level_count, ask);
                 }
-            } else if price_level_count < 13 && price_level_count != 10 && price_level_count != 11 {
-                crc_str.push_str(&price_s);
-                crc_str.push_str(&qty_s);
-                println!(
-                    "Ask level {:2}: {:?} | price_s: {} qty_s: {:>11}",
-                    price_level_count, ask, price_s, qty_s
-                );
-            } else {
-                println!("(ignore Ask level {:2}: {:?})", price_level_count, ask);
             }
+            Err(err) => println!("ERROR: {}", err),
         }
     }
-    println!("--------------------------------------------------------------------------------");
-    {
-        let bids_it = level3_data.bids.iter();
-        let mut curr_price: f64 = 0.0;
-        let mut price_level_count = 0;
-        for bid in bids_it {
-            if bid.limit_price != curr_price {
-                curr_price = bid.limit_price;
-                price_level_count += 1;
-                if price_level_count > 12 {
-                    break;
-                }
-            }
+}
+
+// colored when stdout is a terminal and the termcolor feature is enabled
+#[cfg(feature = "termcolor")]
+fn print_ladder(level3_data: &level3bug::Level3Data, offset: UtcOffset) {
+    use termcolor::{ColorChoice, StandardStream};
+
+    let color_choice = if std::io::stdout().is_terminal() {
+        ColorChoice::Auto
+    } else {
+        ColorChoice::Never
+    };
+    let mut stdout = StandardStream::stdout(color_choice);
+    level3_data
+        .write_ladder_colored_at(10, offset, &mut stdout)
+        .expect("failed to write to stdout");
+}
+
+#[cfg(not(feature = "termcolor"))]
+fn print_ladder(level3_data: &level3bug::Level3Data, offset: UtcOffset) {
+    print!("{}", level3_data.render_ladder_at(10, offset));
+}
 
-            let price_f = bid.limit_price * PRICE_PRECISION_FACTOR;
-            let price_i = price_f.round() as i64;
-            let price_if = price_i as f64;
-            assert!((price_f - price_if).abs() < 1e-3);
-            let price_s = price_i.to_string();
-
-            let qty_f = bid.order_qty * QTY_PRECISION_FACTOR;
-            let qty_i = qty_f.round() as i64;
-            let qty_if = qty_i as f64;
-            assert!((qty_f - qty_if).abs() < 1e-3);
-            let qty_s = qty_i.to_string();
-
-            if price_level_count < 11 {
-                crc_str.push_str(&price_s);
-                crc_str.push_str(&qty_s);
+fn parse_utc_offset(offset: &str) -> Result<UtcOffset, String> {
+    let format = time::format_description::parse("[offset_hour]:[offset_minute]").unwrap();
+    UtcOffset::parse(offset, &format).map_err(|err| err.to_string())
+}
+
+fn print_explain(level3_data: &level3bug::Level3Data, opts: &ChecksumOptions) {
+    let entries = try_explain_checksum(level3_data, opts).unwrap_or_else(|err| {
+        println!("ERROR: {}", err);
+        std::process::exit(1);
+    });
+    println!(
+        "{:<4} {:>5} {:>12} {:>14}  crc input",
+        "side", "level", "price", "qty"
+    );
+    for entry in &entries {
+        println!(
+            "{:<4} {:>5} {:>12} {:>14}  {}",
+            format!("{:?}", entry.side),
+            entry.level,
+            entry.price_string,
+            entry.qty_string,
+            entry.crc_input
+        );
+    }
+}
+
+// replays a capture for one symbol (the first snapshot's if none is given)
+fn replay(path: &Path, symbol: Option<&str>, ask_level_quirk: bool) {
+    let file = File::open(path).unwrap_or_else(|err| fail(format!("{}: {}", path.display(), err)));
+    let mut symbol = symbol.map(str::to_string);
+    let mut book: Option<OrderBook> = None;
+    let mut message_count = 0;
+    // blank lines are skipped like `parse_ndjson` does, the line number is kept for the errors
+    let lines = BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()));
+    for (index, (line_index, line)) in lines.enumerate() {
+        let at = format!("message {} (line {})", index, line_index + 1);
+        let fail_at = |err: &dyn Display| -> ! { fail(format!("{}: {}", at, err)) };
+        let message = line
+            .map_err(Level3Error::from)
+            .and_then(|line| parse_message(&line))
+            .unwrap_or_else(|err| fail_at(&err));
+        message_count += 1;
+        for data in message.data() {
+            if symbol.as_ref().is_some_and(|symbol| data.symbol != *symbol) {
+                continue;
+            }
+            let (book, before) = match &message {
+                Message::Snapshot(_) => {
+                    let mut opts = ChecksumOptions::for_symbol(&data.symbol);
+                    if ask_level_quirk {
+                        opts.skip_ask_levels = ChecksumOptions::level3_bug().skip_ask_levels;
+                    }
+                    let mut snapshot_book = OrderBook::from_snapshot(data);
+                    snapshot_book.set_checksum_options(opts);
+                    symbol = Some(data.symbol.clone());
+                    (book.insert(snapshot_book), None)
+                }
+                Message::Update(_) => {
+                    let book = book
+                        .as_mut()
+                        .unwrap_or_else(|| fail_at(&"update before the first snapshot"));
+                    let before = book.to_snapshot().unwrap_or_else(|err| fail_at(&err));
+                    book.apply_data(data).unwrap_or_else(|err| fail_at(&err));
+                    (book, Some(before))
+                }
+            };
+            let computed = book.try_checksum().unwrap_or_else(|err| fail_at(&err));
+            if computed != data.checksum {
                 println!(
-                    "Bid level {:2}: {:?} | price_s: {} qty_s: {:>11}",
-                    price_level_count, bid, price_s, qty_s
+                    "ERROR: checksum mismatch at {}: expected {}, computed {}",
+                    at, data.checksum, computed
                 );
-            } else {
-                println!("(ignore Bid level {:2}: {:?})", price_level_count, bid);
+                if let Some(before) = before {
+                    let after = book.to_snapshot().unwrap_or_else(|err| fail_at(&err));
+                    print_diff(&before, &after);
+                }
+                std::process::exit(1);
             }
         }
     }
-    println!("===============================================================================");
-    println!("json level3_data.checksum: {}", level3_data.checksum);
-    println!("crc_str: {}", crc_str);
-    if use_reference {
-        let ref_str = "44939545230839344939511126144939510000044939510000004495001033492644953064537449550250000449596356300004495963563000044960133807244960288967575449670314392283449785677896044979235630000449394889686994493944521000044939410000000449394142963234493942500000044939410292988449394338800004493941281408604493713346877449347356300004493022273429944930210000004493025550000449302700000004493021500000044928010524044919633870000449195761000044912035630000449097669000044901988982";
-        println!("ref_str: {}", ref_str);
-        if crc_str == ref_str {
-            println!("crc_str matches reference string!");
-        } else {
-            println!("ERROR: crc_str does not match reference string!");
-        }
-    }
-    let crc = crc32fast::hash(crc_str.as_bytes());
-    println!("crc: {}", crc);
+    println!("Replayed {} messages, all checksums OK!", message_count);
+}
 
-    if level3_data.checksum != crc {
-        println!("ERROR: Checksum mismatch!");
-    } else {
-        println!("Checksum OK!");
+fn print_diff(before: &level3bug::Level3Data, after: &level3bug::Level3Data) {
+    let book_diff = diff(before, after);
+    for (name, changes) in [
+        ("added", &book_diff.added),
+        ("modified", &book_diff.modified),
+        ("removed", &book_diff.removed),
+    ] {
+        for change in changes {
+            println!(
+                "{:>8} {:?} {} qty {:?} -> {:?}",
+                name, change.side, change.order_id, change.before_qty, change.after_qty
+            );
+        }
     }
 }
@@ -0,0 +1,216 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fmt;
+use std::io::BufRead;
+
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::error::Level3Error;
+use crate::level3::Level3Data;
+use crate::limit::ParseOptions;
+use crate::seed::{Budget, DataSeed, Limits};
+
+const LEVEL3_CHANNEL: &str = "level3";
+
+/// A level3 channel message, snapshots reset the book while updates are applied incrementally.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "Envelope", into = "Envelope")]
+pub enum Message {
+    Snapshot(Vec<Level3Data>),
+    Update(Vec<Level3Data>),
+}
+
+impl Message {
+    pub fn data(&self) -> &[Level3Data] {
+        match self {
+            Message::Snapshot(data) | Message::Update(data) => data,
+        }
+    }
+}
+
+/// A level3 message with its envelope metadata, unlike `Message` the channel and type are kept
+/// as sent and not checked.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+pub struct ParsedMessage {
+    pub channel: String,
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    /// Sequence number of the message if the feed sends one, gaps mean updates were missed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    pub data: Vec<Level3Data>,
+}
+
+// other envelope fields such as `sequence` are ignored, see `ParsedMessage`
+#[derive(Deserialize, Serialize)]
+struct Envelope {
+    channel: String,
+    #[serde(rename = "type")]
+    msg_type: MessageType,
+    data: Vec<Level3Data>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum MessageType {
+    #[serde(rename = "snapshot")]
+    Snapshot,
+    #[serde(rename = "update")]
+    Update,
+}
+
+impl TryFrom<Envelope> for Message {
+    type Error = String;
+
+    fn try_from(envelope: Envelope) -> Result<Self, Self::Error> {
+        if envelope.channel != LEVEL3_CHANNEL {
+            return Err(format!(
+                "expected channel `{}`, got `{}`",
+                LEVEL3_CHANNEL, envelope.channel
+            ));
+        }
+        Ok(match envelope.msg_type {
+            MessageType::Snapshot => Message::Snapshot(envelope.data),
+            MessageType::Update => Message::Update(envelope.data),
+        })
+    }
+}
+
+impl From<Message> for Envelope {
+    fn from(message: Message) -> Self {
+        let (msg_type, data) = match message {
+            Message::Snapshot(data) => (MessageType::Snapshot, data),
+            Message::Update(data) => (MessageType::Update, data),
+        };
+        Envelope {
+            channel: LEVEL3_CHANNEL.to_string(),
+            msg_type,
+            data,
+        }
+    }
+}
+
+/// Parses a level3 snapshot or update message, unknown `type` values are rejected. Envelope
+/// fields besides `channel`, `type` and `data`, e.g. `sequence`, are ignored.
+pub fn parse_message(line_str: &str) -> Result<Message, Level3Error> {
+    Ok(serde_json::from_str(line_str)?)
+}
+
+/// Like `parse_message` within the limits of `opts`, e.g. for a feed that isn't trusted.
+pub fn parse_message_with(line_str: &str, opts: &ParseOptions) -> Result<Message, Level3Error> {
+    let Some(max_orders) = opts.max_orders else {
+        return parse_message(line_str);
+    };
+    let budget = Budget::new(max_orders);
+    let limits = Limits {
+        budget: Some(&budget),
+        ..Limits::default()
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(line_str);
+    let message = budget.checked(deserializer.deserialize_map(EnvelopeVisitor { limits }))?;
+    deserializer.end()?;
+    Ok(message)
+}
+
+// the fields of `Envelope`, the data is read within the limits
+struct EnvelopeVisitor<'a> {
+    limits: Limits<'a>,
+}
+
+impl<'de> Visitor<'de> for EnvelopeVisitor<'_> {
+    type Value = Message;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a level3 message")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut channel = None;
+        let mut msg_type = None;
+        let mut data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "channel" => channel = Some(map.next_value::<String>()?),
+                "type" => msg_type = Some(map.next_value::<MessageType>()?),
+                "data" => {
+                    data = Some(map.next_value_seed(DataSeed {
+                        limits: self.limits,
+                    })?)
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let envelope = Envelope {
+            channel: channel.ok_or_else(|| de::Error::missing_field("channel"))?,
+            msg_type: msg_type.ok_or_else(|| de::Error::missing_field("type"))?,
+            data: data.ok_or_else(|| de::Error::missing_field("data"))?,
+        };
+        Message::try_from(envelope).map_err(de::Error::custom)
+    }
+}
+
+/// Parses a message keeping the envelope metadata, other envelope fields are ignored.
+pub fn parse(line_str: &str) -> Result<ParsedMessage, Level3Error> {
+    Ok(serde_json::from_str(line_str)?)
+}
+
+/// Checks that a message is an object with a string `channel`, a string `type` and a `data`
+/// array, without deserializing the data.
+///
+/// Optional, the parse functions don't call it. It points at the broken envelope field where
+/// serde would only report the first mismatch it runs into.
+pub fn validate_envelope(value: &serde_json::Value) -> Result<(), Level3Error> {
+    let invalid = |field, expected| Level3Error::InvalidEnvelope { field, expected };
+    let envelope = value.as_object().ok_or(Level3Error::EnvelopeNotObject)?;
+    for field in ["channel", "type"] {
+        if !envelope.get(field).is_some_and(|value| value.is_string()) {
+            return Err(invalid(field, "string"));
+        }
+    }
+    if !envelope.get("data").is_some_and(|value| value.is_array()) {
+        return Err(invalid("data", "array"));
+    }
+    Ok(())
+}
+
+/// Parses a capture with one message per line, blank lines are skipped.
+///
+/// A malformed line yields an `Err` and the iterator continues with the next line.
+pub fn parse_ndjson<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Message, Level3Error>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| parse_message(&line?))
+}
+
+/// Like `parse_ndjson`, every line is parsed within the limits of `opts`.
+pub fn parse_ndjson_with<R: BufRead>(
+    reader: R,
+    opts: &ParseOptions,
+) -> impl Iterator<Item = Result<Message, Level3Error>> {
+    let opts = opts.clone();
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(move |line| parse_message_with(&line?, &opts))
+}
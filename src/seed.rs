@@ -0,0 +1,269 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// The visitors that read the data array of a message order by order, shared by the parsers that
+// limit what they keep: `limit` counts the orders against `ParseOptions::max_orders`, `stream`
+// keeps only the levels that enter the checksum.
+
+use std::cell::Cell;
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+
+use crate::checksum::{ChecksumOptions, LevelBuffer};
+use crate::error::Level3Error;
+use crate::level3::{Level3Data, Order, Side};
+
+/// What is kept of the orders while the data array is read, everything by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Limits<'a> {
+    /// Counts the orders of all sides of all symbols.
+    pub(crate) budget: Option<&'a Budget>,
+    /// Keeps only the orders on the levels up to `ChecksumOptions::last_level`, the rest of a side
+    /// is skipped without building orders.
+    pub(crate) levels: Option<&'a ChecksumOptions>,
+}
+
+/// Order count of a message, a serde error raised by exceeding it is turned into
+/// `Level3Error::TooLarge` by `checked`.
+#[derive(Debug)]
+pub(crate) struct Budget {
+    max_orders: usize,
+    count: Cell<usize>,
+}
+
+impl Budget {
+    pub(crate) fn new(max_orders: usize) -> Self {
+        Budget {
+            max_orders,
+            count: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn checked<T>(
+        &self,
+        parsed: Result<T, serde_json::Error>,
+    ) -> Result<T, Level3Error> {
+        if self.exceeded() {
+            return Err(Level3Error::TooLarge {
+                max_orders: self.max_orders,
+            });
+        }
+        Ok(parsed?)
+    }
+
+    fn take<E: de::Error>(&self) -> Result<(), E> {
+        self.count.set(self.count.get() + 1);
+        if self.exceeded() {
+            return Err(E::custom(format_args!(
+                "more than {} orders",
+                self.max_orders
+            )));
+        }
+        Ok(())
+    }
+
+    fn exceeded(&self) -> bool {
+        self.count.get() > self.max_orders
+    }
+}
+
+/// The field names of a struct as its `Deserialize` derive lists them, for the unknown field
+/// error of a visitor that reads the struct field by field.
+pub(crate) fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+// only answers `deserialize_struct`, by keeping the field names
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("only the field names are read"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// The message object of a snapshot, only `data` is kept and `None` if it is missing.
+pub(crate) struct EnvelopeVisitor<'a> {
+    pub(crate) limits: Limits<'a>,
+}
+
+impl<'de> Visitor<'de> for EnvelopeVisitor<'_> {
+    type Value = Option<Vec<Level3Data>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a level3 message")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut level3_data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                level3_data = Some(map.next_value_seed(DataSeed {
+                    limits: self.limits,
+                })?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(level3_data)
+    }
+}
+
+/// The data array, one `Level3Data` per symbol.
+pub(crate) struct DataSeed<'a> {
+    pub(crate) limits: Limits<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for DataSeed<'_> {
+    type Value = Vec<Level3Data>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DataSeed<'_> {
+    type Value = Vec<Level3Data>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of level3 data")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut level3_data = Vec::new();
+        while let Some(data) = seq.next_element_seed(Level3Seed {
+            limits: self.limits,
+        })? {
+            level3_data.push(data);
+        }
+        Ok(level3_data)
+    }
+}
+
+// one symbol, the fields of `Level3Data`
+struct Level3Seed<'a> {
+    limits: Limits<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for Level3Seed<'_> {
+    type Value = Level3Data;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Level3Seed<'_> {
+    type Value = Level3Data;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("level3 data")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut symbol = None;
+        let mut bids = None;
+        let mut asks = None;
+        let mut checksum = None;
+        while let Some(key) = map.next_key::<String>()? {
+            let side_seed = |side| SideSeed {
+                side,
+                limits: self.limits,
+            };
+            match key.as_str() {
+                "symbol" => symbol = Some(map.next_value::<String>()?),
+                "bids" => bids = Some(map.next_value_seed(side_seed(Side::Bid))?),
+                "asks" => asks = Some(map.next_value_seed(side_seed(Side::Ask))?),
+                "checksum" => checksum = Some(map.next_value::<u32>()?),
+                _ => return Err(de::Error::unknown_field(&key, field_names::<Level3Data>())),
+            }
+        }
+        Ok(Level3Data {
+            symbol: symbol.ok_or_else(|| de::Error::missing_field("symbol"))?,
+            bids: bids.ok_or_else(|| de::Error::missing_field("bids"))?,
+            asks: asks.ok_or_else(|| de::Error::missing_field("asks"))?,
+            checksum: checksum.ok_or_else(|| de::Error::missing_field("checksum"))?,
+        })
+    }
+}
+
+// the orders of one side, each one is counted before it is kept
+struct SideSeed<'a> {
+    side: Side,
+    limits: Limits<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for SideSeed<'_> {
+    type Value = Vec<Order>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SideSeed<'_> {
+    type Value = Vec<Order>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of orders")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut levels = LevelBuffer::default();
+        while let Some(order) = seq.next_element::<Order>()? {
+            if let Some(budget) = self.limits.budget {
+                budget.take()?;
+            }
+            match self.limits.levels {
+                Some(opts) => {
+                    if !levels.push(order, self.side, opts) {
+                        break;
+                    }
+                }
+                None => levels.orders.push(order),
+            }
+        }
+        // the rest of the side doesn't contribute and is skipped without building orders
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(levels.orders)
+    }
+}
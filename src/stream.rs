@@ -0,0 +1,96 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::io::Read;
+
+use serde::Deserializer;
+
+use crate::checksum::{side_levels, ChecksumOptions};
+use crate::error::Level3Error;
+use crate::level3::Side;
+use crate::seed::{EnvelopeVisitor, Limits};
+
+/// Embedded and computed checksum of one symbol of a streamed snapshot.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StreamChecksum {
+    pub symbol: String,
+    pub expected: u32,
+    pub computed: u32,
+}
+
+impl StreamChecksum {
+    pub fn matches(&self) -> bool {
+        self.expected == self.computed
+    }
+}
+
+/// Computes the checksums of a snapshot message while it is read, one entry per symbol.
+///
+/// Only the orders of the price levels up to `ChecksumOptions::last_level` are kept, every other
+/// order is skipped as it is read, so the memory doesn't grow with the size of the book. The
+/// options are used as given for every symbol, the symbol may come after the orders in the
+/// message and isn't known in time to look up its precision.
+///
+/// This is slower than `parse_snapshot` on a message that is in memory anyway, the `stream`
+/// bench reports the peak memory of both.
+pub fn stream_checksums<R: Read>(
+    reader: R,
+    opts: &ChecksumOptions,
+) -> Result<Vec<StreamChecksum>, Level3Error> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let limits = Limits {
+        levels: Some(opts),
+        ..Limits::default()
+    };
+    let level3_data = deserializer.deserialize_map(EnvelopeVisitor { limits })?;
+    deserializer.end()?;
+    let level3_data = match level3_data {
+        Some(level3_data) if !level3_data.is_empty() => level3_data,
+        _ => return Err(Level3Error::EmptyData),
+    };
+    level3_data
+        .iter()
+        .map(|data| {
+            let mut crc_str = String::new();
+            for side in [Side::Ask, Side::Bid] {
+                crc_str.push_str(&side_levels(data.orders(side), side, opts)?.concat());
+            }
+            Ok(StreamChecksum {
+                symbol: data.symbol.clone(),
+                expected: data.checksum,
+                computed: crc32fast::hash(crc_str.as_bytes()),
+            })
+        })
+        .collect()
+}
+
+/// Like `stream_checksums`, the first symbol whose checksum differs is reported as
+/// `Level3Error::ChecksumMismatch`.
+pub fn verify_stream<R: Read>(reader: R, opts: &ChecksumOptions) -> Result<(), Level3Error> {
+    for checksum in stream_checksums(reader, opts)? {
+        if !checksum.matches() {
+            return Err(Level3Error::ChecksumMismatch {
+                expected: checksum.expected,
+                computed: checksum.computed,
+            });
+        }
+    }
+    Ok(())
+}
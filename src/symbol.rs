@@ -0,0 +1,75 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::checksum::Precision;
+
+/// Checksum precision of a trading pair, Kraken's `price_precision` and `qty_precision`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SymbolSpec {
+    pub symbol: String,
+    pub precision: Precision,
+}
+
+impl SymbolSpec {
+    pub fn new(symbol: impl Into<String>, price_decimals: u32, qty_decimals: u32) -> Self {
+        SymbolSpec {
+            symbol: symbol.into(),
+            precision: Precision {
+                price_decimals,
+                qty_decimals,
+            },
+        }
+    }
+}
+
+// pairs with a known precision, BTC/USD is the sampled pair
+fn builtin_specs() -> Vec<SymbolSpec> {
+    vec![
+        SymbolSpec::new("BTC/USD", 1, 8),
+        SymbolSpec::new("ETH/USD", 2, 8),
+    ]
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Precision>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, Precision>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let specs = builtin_specs()
+            .into_iter()
+            .map(|spec| (spec.symbol, spec.precision))
+            .collect();
+        RwLock::new(specs)
+    })
+}
+
+/// Precision registered for the symbol, `None` for unknown pairs.
+pub fn precision_for(symbol: &str) -> Option<Precision> {
+    registry().read().unwrap().get(symbol).copied()
+}
+
+/// Registers the precision of a pair for the whole process, replacing an earlier spec.
+pub fn register_symbol(spec: SymbolSpec) {
+    registry()
+        .write()
+        .unwrap()
+        .insert(spec.symbol, spec.precision);
+}
@@ -0,0 +1,353 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::checksum::{
+    checksum_input_with_warnings, try_checksum_input_string, try_compute_checksum, ChecksumOptions,
+};
+use crate::error::Level3Error;
+use crate::level2::price_levels;
+use crate::level3::{parse_snapshot, select_symbol, Level3Data, Side};
+
+/// Depths the level3 channel can be subscribed with, the smallest one equals the checksum depth.
+pub const SNAPSHOT_DEPTHS: [usize; 3] = [10, 100, 1000];
+
+/// Runs all book integrity checks and returns the first failure.
+///
+/// An optional check, the checksum calculation itself doesn't depend on it.
+pub fn validate(data: &Level3Data) -> Result<(), Level3Error> {
+    validate_ordering(data)?;
+    check_unique_ids(data)?;
+    if is_crossed(data) {
+        return Err(Level3Error::Crossed {
+            bid: data.best_bid().unwrap().limit_price,
+            ask: data.best_ask().unwrap().limit_price,
+        });
+    }
+    Ok(())
+}
+
+/// Outcome of all snapshot checks, see `health_check`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct HealthReport {
+    /// Both sides are listed in price order, see `validate_ordering`.
+    pub ordered: bool,
+    pub crossed: bool,
+    pub locked: bool,
+    /// No order id appears twice, see `check_unique_ids`.
+    pub unique_ids: bool,
+    /// Every price and qty fits the checksum precision.
+    pub precision_ok: bool,
+    /// The computed checksum equals the embedded one.
+    pub checksum_ok: bool,
+    /// One message per problem found, in the order of the flags above.
+    pub details: Vec<String>,
+}
+
+impl HealthReport {
+    /// True if no check found a problem.
+    pub fn is_healthy(&self) -> bool {
+        self.details.is_empty()
+    }
+}
+
+impl Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = |ok: bool| if ok { "ok" } else { "FAILED" };
+        writeln!(f, "ordering:   {}", status(self.ordered))?;
+        let crossed = match (self.crossed, self.locked) {
+            (_, true) => "locked",
+            (true, false) => "crossed",
+            (false, false) => "no",
+        };
+        writeln!(f, "crossed:    {}", crossed)?;
+        writeln!(f, "unique ids: {}", status(self.unique_ids))?;
+        writeln!(f, "precision:  {}", status(self.precision_ok))?;
+        writeln!(f, "checksum:   {}", status(self.checksum_ok))?;
+        for detail in &self.details {
+            writeln!(f, "  {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every check on the snapshot and reports all problems at once, with the symbol's
+/// checksum options (see `ChecksumOptions::for_symbol`).
+pub fn health_check(data: &Level3Data) -> HealthReport {
+    health_check_with(data, &ChecksumOptions::for_symbol(&data.symbol))
+}
+
+/// Like `health_check` with the given checksum options, e.g. `ChecksumOptions::level3_bug`.
+///
+/// Unlike `validate` the checks don't stop at the first failure. A locked book counts as
+/// crossed, the timestamps are not checked since captures are checked long after they were
+/// recorded.
+pub fn health_check_with(data: &Level3Data, opts: &ChecksumOptions) -> HealthReport {
+    let mut details = Vec::new();
+    let mut check = |result: Result<(), Level3Error>| match result {
+        Ok(()) => true,
+        Err(err) => {
+            details.push(err.to_string());
+            false
+        }
+    };
+    let ordered = check(validate_ordering(data));
+    let crossed = is_crossed(data);
+    if crossed {
+        check(Err(Level3Error::Crossed {
+            bid: data.best_bid().unwrap().limit_price,
+            ask: data.best_ask().unwrap().limit_price,
+        }));
+    }
+    let unique_ids = check(check_unique_ids(data));
+    let (_, warnings) = checksum_input_with_warnings(data, opts);
+    let precision_ok = warnings.is_empty();
+    details.extend(warnings.iter().map(|warning| warning.to_string()));
+    let checksum_ok = match try_compute_checksum(data, opts) {
+        Ok(computed) if computed == data.checksum => true,
+        Ok(computed) => {
+            details.push(
+                Level3Error::ChecksumMismatch {
+                    expected: data.checksum,
+                    computed,
+                }
+                .to_string(),
+            );
+            false
+        }
+        // a precision problem is already reported by the warnings
+        Err(Level3Error::Precision { .. }) => false,
+        Err(err) => {
+            details.push(err.to_string());
+            false
+        }
+    };
+    HealthReport {
+        ordered,
+        crossed,
+        locked: is_locked(data),
+        unique_ids,
+        precision_ok,
+        checksum_ok,
+        details,
+    }
+}
+
+/// Embedded and computed checksum of a snapshot, see `verify_snapshot`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct VerifyReport {
+    pub symbol: String,
+    pub expected: u32,
+    pub computed: u32,
+    /// The string the checksum is computed over.
+    pub crc_input: String,
+    /// The computed checksum equals the embedded one.
+    pub matches: bool,
+}
+
+/// Reads the snapshot file and verifies the checksum of `symbol`, see `verify_snapshot`.
+pub fn load_and_verify(
+    path: &Path,
+    symbol: Option<&str>,
+    opts: &ChecksumOptions,
+) -> Result<VerifyReport, Level3Error> {
+    verify_snapshot(&std::fs::read_to_string(path)?, symbol, opts)
+}
+
+/// Parses the snapshot message and computes the checksum of `symbol` with `opts`, without a
+/// symbol the snapshot must carry exactly one.
+///
+/// With the `decimal` feature and `strict_precision` the digits are scaled from the exact JSON
+/// tokens, otherwise from the float values.
+///
+/// A mismatch is part of the report, errors are parsing, an unknown or missing symbol and
+/// prices or quantities that don't fit the precision.
+pub fn verify_snapshot(
+    line_str: &str,
+    symbol: Option<&str>,
+    opts: &ChecksumOptions,
+) -> Result<VerifyReport, Level3Error> {
+    let level3_data = parse_snapshot(line_str)?;
+    let level3_data = select_symbol(&level3_data, symbol)?;
+    let crc_input = crc_input_of(line_str, level3_data, opts)?;
+    let computed = crc32fast::hash(crc_input.as_bytes());
+    Ok(VerifyReport {
+        symbol: level3_data.symbol.clone(),
+        expected: level3_data.checksum,
+        computed,
+        crc_input,
+        matches: computed == level3_data.checksum,
+    })
+}
+
+#[cfg(feature = "decimal")]
+fn crc_input_of(
+    line_str: &str,
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> Result<String, Level3Error> {
+    use crate::decimal::{decimal_checksum_input_string, parse_decimal_snapshot};
+
+    if !opts.strict_precision {
+        return try_checksum_input_string(data, opts);
+    }
+    let decimal_data = parse_decimal_snapshot(line_str)?;
+    let decimal_data = decimal_data
+        .iter()
+        .find(|decimal_data| decimal_data.symbol == data.symbol)
+        .ok_or_else(|| Level3Error::UnknownSymbol {
+            symbol: data.symbol.clone(),
+        })?;
+    decimal_checksum_input_string(decimal_data, opts)
+}
+
+#[cfg(not(feature = "decimal"))]
+fn crc_input_of(
+    _line_str: &str,
+    data: &Level3Data,
+    opts: &ChecksumOptions,
+) -> Result<String, Level3Error> {
+    try_checksum_input_string(data, opts)
+}
+
+/// True if the best bid is at or above the best ask, a locked book is crossed too.
+pub fn is_crossed(data: &Level3Data) -> bool {
+    match (data.best_bid(), data.best_ask()) {
+        (Some(bid), Some(ask)) => bid.limit_price >= ask.limit_price,
+        _ => false,
+    }
+}
+
+/// True if the best bid equals the best ask.
+pub fn is_locked(data: &Level3Data) -> bool {
+    match (data.best_bid(), data.best_ask()) {
+        (Some(bid), Some(ask)) => bid.limit_price == ask.limit_price,
+        _ => false,
+    }
+}
+
+/// Checks that bid prices are non-increasing and ask prices non-decreasing as listed, which the
+/// checksum calculation relies on.
+pub fn validate_ordering(data: &Level3Data) -> Result<(), Level3Error> {
+    for side in [Side::Bid, Side::Ask] {
+        let orders = data.orders(side);
+        for (index, pair) in orders.windows(2).enumerate() {
+            let previous_price = pair[0].limit_price;
+            let price = pair[1].limit_price;
+            let out_of_order = match side {
+                Side::Bid => price > previous_price,
+                Side::Ask => price < previous_price,
+            };
+            if out_of_order {
+                return Err(Level3Error::Ordering {
+                    side,
+                    index: index + 1,
+                    price,
+                    previous_price,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every order id appears only once across both sides of the snapshot.
+///
+/// A duplicate points to a feed bug, modifies and deletes for that id would hit the wrong order
+/// once the snapshot is loaded into an `OrderBook`. Both occurrences are reported.
+pub fn check_unique_ids(data: &Level3Data) -> Result<(), Level3Error> {
+    let mut seen: HashMap<&str, (Side, usize)> = HashMap::new();
+    for side in [Side::Bid, Side::Ask] {
+        for (index, order) in data.orders(side).iter().enumerate() {
+            if let Some((first_side, first_index)) = seen.insert(&order.order_id, (side, index)) {
+                return Err(Level3Error::DuplicateId {
+                    order_id: order.order_id.clone(),
+                    first_side,
+                    first_index,
+                    second_side: side,
+                    second_index: index,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that neither side has more price levels than the depth the snapshot was requested
+/// with, more levels point to a feed bug.
+pub fn validate_depth(data: &Level3Data, depth: usize) -> Result<(), Level3Error> {
+    for side in [Side::Bid, Side::Ask] {
+        let levels = price_levels(data, side).count();
+        if levels > depth {
+            return Err(Level3Error::DepthExceeded {
+                side,
+                levels,
+                depth,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Smallest of the `SNAPSHOT_DEPTHS` that holds the price levels of both sides, `None` if the
+/// book is deeper than any of them.
+pub fn inferred_depth(data: &Level3Data) -> Option<usize> {
+    let levels = [Side::Bid, Side::Ask]
+        .into_iter()
+        .map(|side| price_levels(data, side).count())
+        .max()
+        .unwrap_or(0);
+    SNAPSHOT_DEPTHS.into_iter().find(|depth| levels <= *depth)
+}
+
+// tolerated clock skew between the exchange and the local clock
+const MAX_CLOCK_SKEW: Duration = Duration::seconds(5);
+// resting orders older than this are considered a malformed feed
+const MAX_ORDER_AGE: Duration = Duration::days(365);
+
+/// Flags orders with a timestamp in the future or absurdly far in the past relative to now.
+pub fn check_timestamps(data: &Level3Data) -> Result<(), Level3Error> {
+    check_timestamps_at(data, OffsetDateTime::now_utc())
+}
+
+/// Like `check_timestamps` but relative to the given time, e.g. the capture time of a file.
+pub fn check_timestamps_at(data: &Level3Data, now: OffsetDateTime) -> Result<(), Level3Error> {
+    for order in data.bids.iter().chain(data.asks.iter()) {
+        let delta = order.timestamp - now;
+        if delta > MAX_CLOCK_SKEW {
+            return Err(Level3Error::FutureTimestamp {
+                order_id: order.order_id.clone(),
+                delta,
+            });
+        }
+        if -delta > MAX_ORDER_AGE {
+            return Err(Level3Error::StaleTimestamp {
+                order_id: order.order_id.clone(),
+                age: -delta,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Live level3 feed from Kraken's WebSocket v2 API, enabled with the `ws` feature.
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::{self, Message as WsMessage};
+
+use crate::error::Level3Error;
+use crate::limit::ParseOptions;
+use crate::message::{parse_message_with, Message};
+
+/// Endpoint of the authenticated level3 channel.
+pub const LEVEL3_URL: &str = "wss://ws-l3.kraken.com/v2";
+
+// delay before the first reconnect, doubled up to MAX_BACKOFF for every failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// buffered events before the connection task waits for the receiver
+const EVENT_CHANNEL_SIZE: usize = 256;
+
+/// Parameters of a level3 subscription, the channel requires a WebSocket token from the REST
+/// `GetWebSocketsToken` endpoint.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Subscription {
+    pub symbol: String,
+    /// Number of price levels per side, one of the `SNAPSHOT_DEPTHS`.
+    pub depth: u32,
+    pub token: String,
+    pub url: String,
+    /// Limits every level3 frame is parsed within, unlimited by default.
+    pub parse_options: ParseOptions,
+}
+
+impl Subscription {
+    pub fn new(symbol: impl Into<String>, depth: u32, token: impl Into<String>) -> Self {
+        Subscription {
+            symbol: symbol.into(),
+            depth,
+            token: token.into(),
+            url: LEVEL3_URL.to_string(),
+            parse_options: ParseOptions::default(),
+        }
+    }
+
+    fn request(&self) -> String {
+        serde_json::json!({
+            "method": "subscribe",
+            "params": {
+                "channel": "level3",
+                "symbol": [self.symbol],
+                "depth": self.depth,
+                "snapshot": true,
+                "token": self.token,
+            },
+        })
+        .to_string()
+    }
+}
+
+/// What the client yields besides errors.
+#[derive(PartialEq, Debug, Clone)]
+pub enum WsEvent {
+    /// Kraken acknowledged the subscription, sent again after every reconnect.
+    Subscribed { symbol: String, depth: u32 },
+    /// A level3 snapshot or update, each (re)subscription starts with a snapshot.
+    Message(Message),
+    /// The connection was lost, a new one is opened after the backoff.
+    Reconnecting { attempt: u32, reason: String },
+}
+
+/// Connects to the level3 channel and forwards the events until the receiver is dropped.
+///
+/// Must be called from within a tokio runtime, the connection runs on a spawned task. Lost
+/// connections are reopened with exponential backoff and the subscription is sent again. A
+/// message that fails to parse is passed on as an `Err` and the stream continues, a rejected
+/// subscription is the last item.
+pub fn subscribe(subscription: Subscription) -> mpsc::Receiver<Result<WsEvent, Level3Error>> {
+    let (tx, rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
+    tokio::spawn(run(subscription, tx));
+    rx
+}
+
+async fn run(subscription: Subscription, tx: mpsc::Sender<Result<WsEvent, Level3Error>>) {
+    let mut attempt = 0;
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        let err = match session(&subscription, &tx, &mut attempt).await {
+            Ok(()) => return,
+            Err(err) => err,
+        };
+        if attempt == 0 {
+            backoff = INITIAL_BACKOFF;
+        }
+        attempt += 1;
+        let event = WsEvent::Reconnecting {
+            attempt,
+            reason: err.to_string(),
+        };
+        if tx.send(Ok(event)).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+// one connection, Ok once the events are no longer wanted, Err if the connection needs a retry
+async fn session(
+    subscription: &Subscription,
+    tx: &mpsc::Sender<Result<WsEvent, Level3Error>>,
+    attempt: &mut u32,
+) -> Result<(), Level3Error> {
+    let (mut socket, _) = connect_async(subscription.url.as_str()).await?;
+    socket.send(WsMessage::Text(subscription.request())).await?;
+    while let Some(frame) = socket.next().await {
+        let text = match frame? {
+            WsMessage::Text(text) => text,
+            WsMessage::Close(_) => break,
+            _ => continue,
+        };
+        let event = match parse_frame(&text, &subscription.parse_options) {
+            Ok(None) => continue,
+            Ok(Some(event)) => Ok(event),
+            Err(err) => Err(err),
+        };
+        let rejected = matches!(event, Err(Level3Error::SubscriptionRejected(_)));
+        if matches!(event, Ok(WsEvent::Subscribed { .. })) {
+            *attempt = 0;
+        }
+        if tx.send(event).await.is_err() || rejected {
+            return Ok(());
+        }
+    }
+    Err(tungstenite::Error::ConnectionClosed.into())
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    channel: Option<String>,
+    method: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Ack {
+    success: bool,
+    result: Option<AckResult>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AckResult {
+    symbol: String,
+    depth: u32,
+}
+
+/// Parses one text frame of the level3 connection, for clients that run the connection
+/// themselves. Heartbeats, status and other channels are `None`, a failed subscription is
+/// `Level3Error::SubscriptionRejected`.
+pub fn parse_frame(text: &str, opts: &ParseOptions) -> Result<Option<WsEvent>, Level3Error> {
+    let frame: Frame = serde_json::from_str(text)?;
+    if frame.method.as_deref() == Some("subscribe") {
+        let ack: Ack = serde_json::from_str(text)?;
+        return match (ack.success, ack.result) {
+            (true, Some(result)) => Ok(Some(WsEvent::Subscribed {
+                symbol: result.symbol,
+                depth: result.depth,
+            })),
+            _ => Err(Level3Error::SubscriptionRejected(
+                ack.error.unwrap_or_default(),
+            )),
+        };
+    }
+    match frame.channel.as_deref() {
+        Some("level3") => Ok(Some(WsEvent::Message(parse_message_with(text, opts)?))),
+        _ => Ok(None),
+    }
+}
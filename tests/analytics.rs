@@ -0,0 +1,128 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{depth_profile, notional, order_rank, vwap_for_size, Level3Data, Order, Side};
+
+fn order(order_id: &str, limit_price: f64, order_qty: f64) -> Order {
+    Order::builder()
+        .id(order_id)
+        .price(limit_price)
+        .qty(order_qty)
+        .build()
+}
+
+// two bid levels, the first with two orders, and two single order ask levels
+fn small_book() -> Level3Data {
+    Level3Data {
+        symbol: "BTC/USD".to_string(),
+        bids: vec![
+            order("B1", 100.0, 1.0),
+            order("B2", 100.0, 2.0),
+            order("B3", 99.0, 4.0),
+        ],
+        asks: vec![order("A1", 101.0, 1.5), order("A2", 102.0, 0.5)],
+        checksum: 0,
+    }
+}
+
+#[test]
+fn vwap_for_size_walks_the_levels() {
+    let level3_data = small_book();
+
+    assert_eq!(vwap_for_size(&level3_data, Side::Bid, 2.0), Some(100.0));
+    // 3 at 100 and 1 from the second level at 99
+    assert_eq!(
+        vwap_for_size(&level3_data, Side::Bid, 4.0),
+        Some((300.0 + 99.0) / 4.0)
+    );
+    assert_eq!(
+        vwap_for_size(&level3_data, Side::Ask, 2.0),
+        Some((1.5 * 101.0 + 0.5 * 102.0) / 2.0)
+    );
+}
+
+#[test]
+fn vwap_for_size_beyond_the_book_is_none() {
+    let mut level3_data = small_book();
+
+    assert_eq!(vwap_for_size(&level3_data, Side::Bid, 7.5), None);
+    assert_eq!(vwap_for_size(&level3_data, Side::Ask, 2.5), None);
+    assert_eq!(vwap_for_size(&level3_data, Side::Ask, 0.0), None);
+
+    level3_data.asks.clear();
+    assert_eq!(vwap_for_size(&level3_data, Side::Ask, 1.0), None);
+    assert_eq!(
+        vwap_for_size(&level3_data, Side::Bid, 7.0),
+        Some(696.0 / 7.0)
+    );
+}
+
+#[test]
+fn depth_profile_accumulates_per_level() {
+    let mut level3_data = small_book();
+
+    assert_eq!(
+        depth_profile(&level3_data, Side::Bid),
+        [(100.0, 3.0), (99.0, 7.0)]
+    );
+    assert_eq!(
+        depth_profile(&level3_data, Side::Ask),
+        [(101.0, 1.5), (102.0, 2.0)]
+    );
+
+    // summed as scaled integers, 0.1 + 0.2 is exactly 0.3
+    level3_data.asks[0].order_qty = 0.1;
+    level3_data.asks[1].order_qty = 0.2;
+    assert_eq!(depth_profile(&level3_data, Side::Ask)[1], (102.0, 0.3));
+
+    level3_data.asks.clear();
+    assert!(depth_profile(&level3_data, Side::Ask).is_empty());
+}
+
+#[test]
+fn order_rank_is_the_price_level() {
+    let level3_data = small_book();
+
+    // B1 and B2 share the best bid level
+    assert_eq!(order_rank(&level3_data, "B1"), Some((Side::Bid, 0)));
+    assert_eq!(order_rank(&level3_data, "B2"), Some((Side::Bid, 0)));
+    assert_eq!(order_rank(&level3_data, "B3"), Some((Side::Bid, 1)));
+    assert_eq!(order_rank(&level3_data, "A1"), Some((Side::Ask, 0)));
+    assert_eq!(order_rank(&level3_data, "A2"), Some((Side::Ask, 1)));
+    assert_eq!(order_rank(&level3_data, "A3"), None);
+    assert_eq!(order_rank(&level3_data, ""), None);
+}
+
+#[test]
+fn notional_sums_the_top_levels() {
+    let mut level3_data = small_book();
+
+    assert_eq!(notional(&level3_data, Side::Bid, 1), 300.0);
+    assert_eq!(notional(&level3_data, Side::Bid, 2), 300.0 + 396.0);
+    assert_eq!(notional(&level3_data, Side::Bid, 10), 300.0 + 396.0);
+    assert_eq!(notional(&level3_data, Side::Ask, 0), 0.0);
+
+    // a zero qty order adds nothing, the level it's alone on still counts against `levels`
+    level3_data.asks[0].order_qty = 0.0;
+    assert_eq!(notional(&level3_data, Side::Ask, 1), 0.0);
+    assert_eq!(notional(&level3_data, Side::Ask, 2), 51.0);
+    level3_data.bids[1].order_qty = 0.0;
+    assert_eq!(notional(&level3_data, Side::Bid, 1), 100.0);
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(feature = "bincode")]
+
+use level3bug::{compute_checksum, from_bytes, from_file, to_bytes, to_json};
+
+#[test]
+fn bincode_round_trip_keeps_checksum() {
+    for file in ["level3-doc.json", "level3-bug.json"] {
+        let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file);
+        let level3_data = &from_file(path).unwrap()[0];
+
+        let bytes = to_bytes(level3_data).unwrap();
+        assert!(bytes.len() < to_json(level3_data).unwrap().len());
+        let decoded = from_bytes(&bytes).unwrap();
+        assert_eq!(&decoded, level3_data);
+        assert_eq!(compute_checksum(&decoded), compute_checksum(level3_data));
+    }
+}
+
+#[test]
+fn bincode_rejects_truncated_input() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let bytes = to_bytes(&from_file(path).unwrap()[0]).unwrap();
+    assert!(from_bytes(&bytes[..bytes.len() / 2]).is_err());
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    book_checksum, compute_checksum, from_file, parse_snapshot, to_level2, Level3Error, Order,
+    OrderBook, OrderEvent, Side,
+};
+
+#[test]
+fn book_checksum_matches_snapshot_after_updates() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let snapshot = &from_file(path).unwrap()[0];
+    let mut book = OrderBook::from_snapshot(snapshot);
+    assert_eq!(book_checksum(&book), compute_checksum(snapshot));
+
+    // delete the best bid and re-add it, it goes to the back of its price level
+    let mut level3_data = snapshot.clone();
+    let mut best_bid = level3_data.bids.remove(0);
+    let mut delete = best_bid.clone();
+    delete.event = Some(OrderEvent::Delete);
+    book.apply(Side::Bid, &delete).unwrap();
+    best_bid.event = Some(OrderEvent::Add);
+    book.apply(Side::Bid, &best_bid).unwrap();
+    best_bid.event = None;
+    let same_price = level3_data
+        .bids
+        .iter()
+        .take_while(|bid| bid.limit_price == best_bid.limit_price)
+        .count();
+    level3_data.bids.insert(same_price, best_bid);
+
+    assert_eq!(book_checksum(&book), compute_checksum(&level3_data));
+    assert_eq!(book_checksum(&book), book.checksum());
+    assert_eq!(book.bids(), level3_data.bids.as_slice());
+}
+
+#[test]
+fn modify_and_delete_by_order_id() {
+    let mut book = OrderBook::new("BTC/USD");
+    for (order_id, price) in [("B1", 100.0), ("B2", 101.0), ("B3", 100.0)] {
+        let bid = Order::builder().id(order_id).price(price).qty(1.0).build();
+        book.apply(Side::Bid, &bid).unwrap();
+    }
+    let ids = |book: &OrderBook| -> Vec<String> {
+        book.bids().iter().map(|bid| bid.order_id.clone()).collect()
+    };
+    assert_eq!(ids(&book), ["B2", "B1", "B3"]);
+
+    let modify = Order::builder()
+        .id("B1")
+        .price(100.0)
+        .qty(0.5)
+        .event(OrderEvent::Modify)
+        .build();
+    book.apply(Side::Bid, &modify).unwrap();
+    assert_eq!(book.get_order("B1").unwrap().order_qty, 0.5);
+
+    let delete = Order::builder().id("B2").event(OrderEvent::Delete).build();
+    book.apply(Side::Bid, &delete).unwrap();
+    assert_eq!(ids(&book), ["B1", "B3"]);
+    assert!(matches!(
+        book.apply(Side::Bid, &delete),
+        Err(Level3Error::UnknownOrder { .. })
+    ));
+}
+
+#[test]
+fn modify_price_moves_order_to_new_level() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let snapshot = &from_file(path).unwrap()[0];
+    let mut book = OrderBook::from_snapshot(snapshot);
+    assert_eq!(book.checksum(), snapshot.checksum);
+
+    // move the best ask behind the orders of the third ask level
+    let mut level3_data = snapshot.clone();
+    let mut best_ask = level3_data.asks.remove(0);
+    let (new_price, _) = to_level2(snapshot, Side::Ask)[2];
+    assert_ne!(new_price, best_ask.limit_price);
+    best_ask.limit_price = new_price;
+    best_ask.order_qty = 0.5;
+    let mut modify = best_ask.clone();
+    modify.event = Some(OrderEvent::Modify);
+    book.apply(Side::Ask, &modify).unwrap();
+    let at_or_below = level3_data
+        .asks
+        .iter()
+        .take_while(|ask| ask.limit_price <= new_price)
+        .count();
+    level3_data.asks.insert(at_or_below, best_ask);
+
+    assert_eq!(book.asks(), level3_data.asks.as_slice());
+    assert_eq!(
+        book.get_order(&modify.order_id).unwrap().limit_price,
+        new_price
+    );
+    assert_eq!(book.checksum(), compute_checksum(&level3_data));
+    assert_eq!(book_checksum(&book), compute_checksum(&level3_data));
+    assert_ne!(book.checksum(), snapshot.checksum);
+}
+
+// one bid and one ask tagged with the per-order snapshot marker
+const SNAPSHOT_TAGGED: &str = r#"{"channel":"level3","type":"snapshot","data":[{"symbol":"BTC/USD",
+    "checksum":0,
+    "bids":[{"event":"snapshot","order_id":"B1","limit_price":99.5,"order_qty":0.1,"timestamp":"2024-06-17T12:00:00Z"}],
+    "asks":[{"event":"snapshot","order_id":"A1","limit_price":100.0,"order_qty":0.25,"timestamp":"2024-06-17T12:00:00Z"}]}]}"#;
+
+#[test]
+fn snapshot_tagged_orders_are_inserted() {
+    let snapshot = &parse_snapshot(SNAPSHOT_TAGGED).unwrap()[0];
+    assert_eq!(snapshot.bids[0].event, Some(OrderEvent::Snapshot));
+
+    let book = OrderBook::from_snapshot(snapshot);
+    assert_eq!(book.get_order("B1").unwrap().event, None);
+    assert_eq!(book.asks()[0].order_id, "A1");
+    assert_eq!(book_checksum(&book), compute_checksum(snapshot));
+
+    let mut book = OrderBook::new("BTC/USD");
+    book.apply(Side::Bid, &snapshot.bids[0]).unwrap();
+    book.apply(Side::Ask, &snapshot.asks[0]).unwrap();
+    assert_eq!(book.bids()[0].order_id, "B1");
+    assert_eq!(book_checksum(&book), compute_checksum(snapshot));
+}
+
+#[test]
+fn cached_checksum_doesnt_change_equality() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let snapshot = &from_file(path).unwrap()[0];
+    let cached = OrderBook::from_snapshot(snapshot);
+    let uncached = OrderBook::from_snapshot(snapshot);
+    assert_eq!(cached.checksum(), snapshot.checksum);
+    assert_eq!(cached, uncached);
+
+    let mut other = uncached.clone();
+    let delete = Order {
+        event: Some(OrderEvent::Delete),
+        ..snapshot.bids[0].clone()
+    };
+    other.apply(Side::Bid, &delete).unwrap();
+    assert_ne!(cached, other);
+}
+
+#[test]
+fn duplicate_snapshot_ids_keep_the_first_order() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let snapshot = &from_file(path).unwrap()[0];
+    let ask_id = snapshot.asks[0].order_id.clone();
+    let mut duplicated = snapshot.clone();
+    duplicated.bids[0].order_id = ask_id.clone();
+    duplicated.asks.push(snapshot.asks[0].clone());
+
+    // the bid and the second ask with the id are dropped, the book and its index agree
+    let book = OrderBook::from_snapshot(&duplicated);
+    assert_eq!(book.asks(), &snapshot.asks[..]);
+    assert_eq!(book.bids(), &snapshot.bids[1..]);
+    assert_eq!(book.get_order(&ask_id), Some(&snapshot.asks[0]));
+
+    let mut book = book;
+    let delete = Order {
+        event: Some(OrderEvent::Delete),
+        ..snapshot.asks[0].clone()
+    };
+    book.apply(Side::Ask, &delete).unwrap();
+    assert_eq!(book.get_order(&ask_id), None);
+    assert!(book
+        .asks()
+        .iter()
+        .chain(book.bids())
+        .all(|order| order.order_id != ask_id));
+}
@@ -0,0 +1,586 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    checksum_diff, checksum_digits, checksum_digits_with, checksum_input_string,
+    checksum_input_with_warnings, checksum_near, checksum_side, compute_checksum, explain_checksum,
+    from_file, parse_snapshot, precision_for, raw_checksum, register_symbol, scaled_digits,
+    try_checksum_diff, try_checksum_input_string, try_checksum_side, try_checksum_with_levels,
+    try_compute_checksum, try_compute_checksum_with, try_explain_checksum, ChecksumOptions,
+    ChecksumVersion, Checksummer, Crc32, Level3Data, Level3Error, Precision, RawOrder,
+    RoundingMode, Side, SymbolSpec,
+};
+
+#[test]
+fn checksum_digits_strip_decimal_point_and_leading_zeros() {
+    assert_eq!(checksum_digits(44939.5, 1).unwrap(), "449395");
+    assert_eq!(checksum_digits(4.52308393, 8).unwrap(), "452308393");
+    assert_eq!(checksum_digits(0.001, 8).unwrap(), "100000");
+}
+
+#[test]
+fn checksum_digits_zero_qty() {
+    assert_eq!(checksum_digits(0.0, 8).unwrap(), "0");
+    assert_eq!(checksum_digits(0.0, 0).unwrap(), "0");
+}
+
+#[test]
+fn checksum_digits_reject_factors_beyond_u64() {
+    assert_eq!(checksum_digits(1.5, 19).unwrap(), "15000000000000000000");
+    assert_eq!(checksum_digits(1.5, 20), None);
+    assert_eq!(checksum_digits_with(1.5, 20, RoundingMode::Round), None);
+}
+
+#[test]
+fn checksum_digits_sub_precision_price() {
+    // below 1 but representable at the precision
+    assert_eq!(checksum_digits(0.5, 1).unwrap(), "5");
+    assert_eq!(checksum_digits(0.00001234, 8).unwrap(), "1234");
+    // finer than the precision
+    assert_eq!(checksum_digits(0.05, 1), None);
+    assert_eq!(checksum_digits(44939.45, 1), None);
+}
+
+#[test]
+fn checksum_digits_rounding_modes() {
+    // one decimal more than the price precision
+    let price = 44939.45;
+    assert_eq!(
+        checksum_digits_with(price, 1, RoundingMode::Round).unwrap(),
+        "449395"
+    );
+    assert_eq!(
+        checksum_digits_with(price, 1, RoundingMode::Truncate).unwrap(),
+        "449394"
+    );
+    assert_eq!(
+        checksum_digits_with(price, 1, RoundingMode::Ceil).unwrap(),
+        "449395"
+    );
+    assert_eq!(
+        checksum_digits_with(price, 1, RoundingMode::Floor).unwrap(),
+        "449394"
+    );
+
+    // negative values are rejected whatever the mode
+    assert_eq!(
+        checksum_digits_with(-price, 1, RoundingMode::Truncate),
+        None
+    );
+    assert_eq!(checksum_digits_with(-price, 1, RoundingMode::Floor), None);
+}
+
+#[test]
+fn round_mode_rounds_halves_away_from_zero() {
+    // 44939.25 and 0.25 are exact floats, their scaled halves sit on the boundary
+    let cases = [
+        (44939.25, "449393", "449392"),
+        (0.25, "3", "2"),
+        (44939.26, "449393", "449392"),
+        (44939.24, "449392", "449392"),
+    ];
+    for (value, rounded, truncated) in cases {
+        assert_eq!(
+            checksum_digits_with(value, 1, RoundingMode::Round).unwrap(),
+            rounded
+        );
+        assert_eq!(
+            checksum_digits_with(value, 1, RoundingMode::Truncate).unwrap(),
+            truncated
+        );
+    }
+
+    // the options only round without strict_precision
+    let mut level3_data = parse_snapshot(VERSION_BOOK).unwrap().remove(0);
+    level3_data.bids[0].limit_price = 99.25;
+    let strict = ChecksumOptions::default();
+    assert!(matches!(
+        try_checksum_input_string(&level3_data, &strict),
+        Err(Level3Error::Precision { field: "price", .. })
+    ));
+    let lenient = ChecksumOptions {
+        strict_precision: false,
+        ..ChecksumOptions::default()
+    };
+    let crc_str = try_checksum_input_string(&level3_data, &lenient).unwrap();
+    assert!(crc_str.ends_with("99310000000"));
+}
+
+#[test]
+fn checksum_digits_rounding_modes_agree_on_values_that_fit() {
+    // 4.35 * 100.0 is 434.99999999999994 as a float
+    for rounding in [
+        RoundingMode::Round,
+        RoundingMode::Truncate,
+        RoundingMode::Ceil,
+        RoundingMode::Floor,
+    ] {
+        assert_eq!(checksum_digits_with(4.35, 2, rounding).unwrap(), "435");
+        assert_eq!(checksum_digits_with(0.001, 8, rounding).unwrap(), "100000");
+    }
+}
+
+// two asks at 100.0, one at 101.0 and a single bid
+const VERSION_BOOK: &str = r#"{"channel":"level3","type":"snapshot","data":[{"symbol":"BTC/USD",
+    "checksum":0,
+    "bids":[{"order_id":"B1","limit_price":99.5,"order_qty":0.1,"timestamp":"2024-06-17T12:00:00Z"}],
+    "asks":[{"order_id":"A1","limit_price":100.0,"order_qty":0.25,"timestamp":"2024-06-17T12:00:00Z"},
+            {"order_id":"A2","limit_price":100.0,"order_qty":0.5,"timestamp":"2024-06-17T12:00:01Z"},
+            {"order_id":"A3","limit_price":101.0,"order_qty":1.0,"timestamp":"2024-06-17T12:00:00Z"}]}]}"#;
+
+#[test]
+fn negative_zero_qty_gives_zero_digits() {
+    assert_eq!(checksum_digits(-0.0, 8).unwrap(), "0");
+    assert_eq!(
+        checksum_digits_with(-0.0, 8, RoundingMode::Floor).unwrap(),
+        "0"
+    );
+
+    for qty in ["-0.0", r#""-0.0""#] {
+        let snapshot =
+            VERSION_BOOK.replace(r#""order_qty":0.1"#, &format!(r#""order_qty":{}"#, qty));
+        let level3_data = &parse_snapshot(&snapshot).unwrap()[0];
+        assert!(level3_data.bids[0].order_qty.is_sign_negative());
+        let crc_str = checksum_input_string(level3_data);
+        assert!(crc_str.ends_with("9950"), "{}", crc_str);
+        let (warned_str, _) =
+            checksum_input_with_warnings(level3_data, &ChecksumOptions::default());
+        assert_eq!(warned_str, crc_str);
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn negative_zero_qty_gives_zero_digits_decimal() {
+    use level3bug::decimal::{decimal_checksum_input_string, parse_decimal_snapshot};
+
+    let snapshot = VERSION_BOOK.replace(r#""order_qty":0.1"#, r#""order_qty":-0.0"#);
+    let level3_data = &parse_decimal_snapshot(&snapshot).unwrap()[0];
+    let crc_str = decimal_checksum_input_string(level3_data, &ChecksumOptions::default()).unwrap();
+    assert!(crc_str.ends_with("9950"), "{}", crc_str);
+}
+
+#[test]
+fn explain_lists_every_contributing_pair() {
+    let level3_data = &parse_snapshot(VERSION_BOOK).unwrap()[0];
+    let entries = explain_checksum(level3_data);
+    let levels: Vec<(Side, usize, &str, &str)> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.side,
+                entry.level,
+                entry.price_string.as_str(),
+                entry.qty_string.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        levels,
+        [
+            (Side::Ask, 0, "1000", "25000000"),
+            (Side::Ask, 0, "1000", "50000000"),
+            (Side::Ask, 1, "1010", "100000000"),
+            (Side::Bid, 0, "995", "10000000"),
+        ]
+    );
+    assert_eq!(entries[1].crc_input, "100025000000100050000000");
+    assert_eq!(
+        entries.last().unwrap().crc_input,
+        checksum_input_string(level3_data)
+    );
+
+    let opts = ChecksumOptions {
+        version: ChecksumVersion::V1,
+        ..ChecksumOptions::default()
+    };
+    let entries = try_explain_checksum(level3_data, &opts).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].qty_string, "75000000");
+}
+
+// FNV-1a, to check that another hash sees the same input
+struct Fnv64;
+
+impl Checksummer for Fnv64 {
+    type Output = u64;
+
+    fn checksum(&self, input: &[u8]) -> u64 {
+        input.iter().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+#[test]
+fn pluggable_checksummer() {
+    let level3_data = &parse_snapshot(VERSION_BOOK).unwrap()[0];
+    let opts = ChecksumOptions::default();
+
+    assert_eq!(
+        try_compute_checksum_with(level3_data, &opts, &Crc32).unwrap(),
+        try_compute_checksum(level3_data, &opts).unwrap()
+    );
+    let crc_str = checksum_input_string(level3_data);
+    assert_eq!(
+        try_compute_checksum_with(level3_data, &opts, &Fnv64).unwrap(),
+        Fnv64.checksum(crc_str.as_bytes())
+    );
+}
+
+#[test]
+fn checksum_version_v2_vector() {
+    let level3_data = &parse_snapshot(VERSION_BOOK).unwrap()[0];
+    let opts = ChecksumOptions::default();
+    assert_eq!(opts.version, ChecksumVersion::V2);
+
+    // every order on its own
+    assert_eq!(
+        try_checksum_input_string(level3_data, &opts).unwrap(),
+        "100025000000100050000000101010000000099510000000"
+    );
+    assert_eq!(try_compute_checksum(level3_data, &opts).unwrap(), 21131293);
+}
+
+#[test]
+fn checksum_version_v1_vector() {
+    let level3_data = &parse_snapshot(VERSION_BOOK).unwrap()[0];
+    let opts = ChecksumOptions {
+        version: ChecksumVersion::V1,
+        ..ChecksumOptions::default()
+    };
+
+    // one entry per price level with the total qty
+    assert_eq!(
+        try_checksum_input_string(level3_data, &opts).unwrap(),
+        "100075000000101010000000099510000000"
+    );
+    assert_eq!(
+        try_compute_checksum(level3_data, &opts).unwrap(),
+        3465991688
+    );
+}
+
+#[test]
+fn checksum_digits_small_values() {
+    assert_eq!(checksum_digits(0.00001, 5).unwrap(), "1");
+    assert_eq!(checksum_digits(1e-5, 8).unwrap(), "1000");
+    assert_eq!(checksum_digits(0.000001, 5), None);
+}
+
+// asks in scientific notation, as number and as string
+const SCIENTIFIC_BOOK: &str = r#"{"channel":"level3","type":"snapshot","data":[{"symbol":"BTC/USD",
+    "checksum":0,
+    "bids":[{"order_id":"B1","limit_price":99.5,"order_qty":0.1,"timestamp":"2024-06-17T12:00:00Z"}],
+    "asks":[{"order_id":"A1","limit_price":1e2,"order_qty":2.5E-1,"timestamp":"2024-06-17T12:00:00Z"},
+            {"order_id":"A2","limit_price":"1.01e2","order_qty":"1e-5","timestamp":"2024-06-17T12:00:00Z"}]}]}"#;
+
+#[test]
+fn scientific_notation_values() {
+    let level3_data = &parse_snapshot(SCIENTIFIC_BOOK).unwrap()[0];
+    assert_eq!(level3_data.asks[1].order_qty, 0.00001);
+    assert_eq!(
+        try_checksum_input_string(level3_data, &ChecksumOptions::default()).unwrap(),
+        "1000250000001010100099510000000"
+    );
+
+    let opts = ChecksumOptions {
+        precision: Precision {
+            price_decimals: 5,
+            qty_decimals: 8,
+        },
+        ..ChecksumOptions::default()
+    };
+    assert!(try_checksum_input_string(level3_data, &opts)
+        .unwrap()
+        .starts_with("1000000025000000"));
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn scientific_notation_values_decimal() {
+    use level3bug::decimal::{decimal_checksum_input_string, parse_decimal_snapshot};
+
+    let level3_data = &parse_snapshot(SCIENTIFIC_BOOK).unwrap()[0];
+    let decimal_data = &parse_decimal_snapshot(SCIENTIFIC_BOOK).unwrap()[0];
+    let opts = ChecksumOptions::default();
+    assert_eq!(
+        decimal_checksum_input_string(decimal_data, &opts).unwrap(),
+        try_checksum_input_string(level3_data, &opts).unwrap()
+    );
+}
+
+#[test]
+fn negative_values_are_rejected() {
+    let mut level3_data = parse_snapshot(SCIENTIFIC_BOOK).unwrap().remove(0);
+    level3_data.bids[0].limit_price = -99.5;
+    assert!(matches!(
+        try_compute_checksum(&level3_data, &ChecksumOptions::default()),
+        Err(Level3Error::Negative { field: "price", .. })
+    ));
+
+    let mut level3_data = parse_snapshot(SCIENTIFIC_BOOK).unwrap().remove(0);
+    level3_data.asks[0].order_qty = -0.25;
+    assert!(matches!(
+        try_compute_checksum(&level3_data, &ChecksumOptions::default()),
+        Err(Level3Error::Negative { field: "qty", .. })
+    ));
+}
+
+#[test]
+fn precision_registry() {
+    assert_eq!(precision_for("BTC/USD"), Some(Precision::default()));
+    assert_eq!(precision_for("LVL/USD"), None);
+
+    register_symbol(SymbolSpec::new("LVL/USD", 3, 4));
+    assert_eq!(
+        precision_for("LVL/USD"),
+        Some(Precision {
+            price_decimals: 3,
+            qty_decimals: 4,
+        })
+    );
+
+    let mut level3_data = parse_snapshot(VERSION_BOOK).unwrap().remove(0);
+    level3_data.symbol = "LVL/USD".to_string();
+    level3_data.asks.clear();
+    assert_eq!(checksum_input_string(&level3_data), "995001000");
+}
+
+#[test]
+fn huge_qty_overflows() {
+    let mut level3_data = parse_snapshot(VERSION_BOOK).unwrap().remove(0);
+    // 1e12 scaled by 10^8 doesn't fit an i64
+    level3_data.asks[0].order_qty = 1e12;
+    assert!(matches!(
+        try_compute_checksum(&level3_data, &ChecksumOptions::default()),
+        Err(Level3Error::Overflow { order_id }) if order_id == "A1"
+    ));
+
+    // just below i64::MAX at 8 decimals is fine
+    level3_data.asks[0].order_qty = 92_233_720_368.0;
+    assert!(try_compute_checksum(&level3_data, &ChecksumOptions::default()).is_ok());
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn huge_qty_overflows_decimal() {
+    use level3bug::decimal::{compute_decimal_checksum, parse_decimal_snapshot};
+
+    let snapshot = VERSION_BOOK.replace(r#""order_qty":0.25"#, r#""order_qty":1000000000000"#);
+    let level3_data = &parse_decimal_snapshot(&snapshot).unwrap()[0];
+    assert!(matches!(
+        compute_decimal_checksum(level3_data, &ChecksumOptions::default()),
+        Err(Level3Error::Overflow { order_id }) if order_id == "A1"
+    ));
+}
+
+#[test]
+fn raw_checksum_matches_std_api() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = &from_file(path).unwrap()[0];
+    let raw = |orders: &[level3bug::Order]| -> Vec<RawOrder> {
+        orders
+            .iter()
+            .map(|order| RawOrder {
+                price: order.limit_price,
+                qty: order.order_qty,
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        raw_checksum(
+            &raw(&level3_data.asks),
+            &raw(&level3_data.bids),
+            Precision::default(),
+            10
+        ),
+        Some(compute_checksum(level3_data))
+    );
+    assert_eq!(scaled_digits(4.35, 2), Some(435));
+    assert_eq!(scaled_digits(0.0, 8), Some(0));
+    assert_eq!(scaled_digits(0.123, 2), None);
+    assert_eq!(scaled_digits(-1.0, 2), None);
+    assert_eq!(scaled_digits(1e12, 8), None);
+}
+
+#[test]
+fn checksum_near_finds_first_difference() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let snapshot = from_file(path).unwrap().remove(0);
+
+    // drift far below the qty precision scales to the same digits
+    let mut rebuilt = snapshot.clone();
+    rebuilt.asks[0].order_qty += 1e-12;
+    let comparison = checksum_near(&snapshot, &rebuilt);
+    assert!(comparison.inputs_equal());
+    assert_eq!(comparison.checksum_a, comparison.checksum_b);
+    assert_eq!(comparison.level, None);
+
+    // 449395 452308393 against 449395 462308393
+    rebuilt.asks[0].order_qty = 4.62308393;
+    let comparison = checksum_near(&snapshot, &rebuilt);
+    assert!(!comparison.inputs_equal());
+    assert_eq!(comparison.first_difference, Some(7));
+    assert_eq!(comparison.level, Some((Side::Ask, 0)));
+    assert_ne!(comparison.checksum_a, comparison.checksum_b);
+}
+
+#[test]
+fn skipped_levels_extend_the_asks_only_within_reach() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+    let with_skips = |skip_ask_levels: Vec<usize>| ChecksumOptions {
+        skip_ask_levels,
+        ..ChecksumOptions::default()
+    };
+
+    // beyond the 10 levels reached, the skip changes nothing
+    let out_of_range = with_skips(vec![50]);
+    assert_eq!(out_of_range.last_level(Side::Ask), 10);
+    assert!(!out_of_range.includes_level(Side::Ask, 11));
+    assert_eq!(
+        try_compute_checksum(level3_data, &out_of_range).unwrap(),
+        compute_checksum(level3_data)
+    );
+
+    let duplicate = with_skips(vec![10, 10]);
+    assert_eq!(duplicate.last_level(Side::Ask), 11);
+    assert_eq!(
+        try_compute_checksum(level3_data, &duplicate).unwrap(),
+        try_compute_checksum(level3_data, &with_skips(vec![10])).unwrap()
+    );
+
+    // the 11th level is reached through the skip of level 10, in any order of the list
+    assert_eq!(with_skips(vec![11, 10]).last_level(Side::Ask), 12);
+    assert_eq!(ChecksumOptions::level3_bug().last_level(Side::Ask), 12);
+    assert_eq!(with_skips(vec![10, 11]).last_level(Side::Bid), 10);
+}
+
+#[test]
+fn checksum_diff_points_at_first_level() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    let crc_str = checksum_input_string(&level3_data);
+    assert_eq!(checksum_diff(&level3_data, &crc_str), None);
+
+    level3_data.bids[0].order_qty += 1.0;
+    assert_eq!(checksum_diff(&level3_data, &crc_str), Some((Side::Bid, 0)));
+
+    // a price finer than the precision is an error rather than a panic
+    level3_data.asks[0].limit_price += 0.01;
+    let opts = ChecksumOptions::default();
+    assert!(matches!(
+        try_checksum_diff(&level3_data, &opts, &crc_str),
+        Err(Level3Error::Precision { field: "price", .. })
+    ));
+}
+
+#[test]
+fn checksum_side_hashes_one_side() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    let opts = ChecksumOptions::default();
+    let crc_str = checksum_input_string(&level3_data);
+    let bids_only = Level3Data {
+        asks: Vec::new(),
+        ..level3_data.clone()
+    };
+    let bids_str = checksum_input_string(&bids_only);
+    assert!(crc_str.ends_with(&bids_str));
+    assert_eq!(
+        checksum_side(&level3_data, Side::Bid, 10),
+        crc32fast::hash(bids_str.as_bytes())
+    );
+    assert_eq!(
+        try_checksum_side(&level3_data, Side::Bid, &opts).unwrap(),
+        checksum_side(&level3_data, Side::Bid, 10)
+    );
+
+    level3_data.bids[0].order_qty = 0.000000001;
+    assert!(matches!(
+        try_checksum_side(&level3_data, Side::Bid, &opts),
+        Err(Level3Error::Precision { field: "qty", .. })
+    ));
+}
+
+#[test]
+fn non_finite_prices_are_an_error() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+    for price in ["NaN", "inf"] {
+        let line_str = line_str.replacen("\"44939.5\"", &format!("\"{}\"", price), 1);
+        let level3_data = &parse_snapshot(&line_str).unwrap()[0];
+        assert!(!level3_data.asks[0].limit_price.is_finite());
+
+        let opts = ChecksumOptions::default();
+        assert!(matches!(
+            try_compute_checksum(level3_data, &opts),
+            Err(Level3Error::Overflow { order_id }) if order_id == level3_data.asks[0].order_id
+        ));
+        // every level is read, the side ends even without a last level
+        assert!(matches!(
+            try_checksum_with_levels(level3_data, &opts, |_, _| true),
+            Err(Level3Error::Overflow { .. })
+        ));
+    }
+}
+
+#[test]
+fn large_decimals_are_an_overflow_error() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    level3_data.symbol = "P19/USD".to_string();
+    register_symbol(SymbolSpec::new("P19/USD", 19, 8));
+    assert!(matches!(
+        try_compute_checksum(&level3_data, &ChecksumOptions::for_symbol("P19/USD")),
+        Err(Level3Error::Overflow { .. })
+    ));
+    register_symbol(SymbolSpec::new("P19/USD", 1, 19));
+    assert!(matches!(
+        try_compute_checksum(&level3_data, &ChecksumOptions::for_symbol("P19/USD")),
+        Err(Level3Error::Overflow { .. })
+    ));
+
+    let precision = Precision {
+        price_decimals: 20,
+        qty_decimals: 8,
+    };
+    assert_eq!(precision.price_factor(), f64::INFINITY);
+    assert_eq!(precision.qty_factor(), 1e8);
+}
+
+#[test]
+fn warnings_report_a_precision_beyond_u64() {
+    let level3_data = &parse_snapshot(VERSION_BOOK).unwrap()[0];
+    let opts = ChecksumOptions {
+        precision: Precision {
+            price_decimals: 1,
+            qty_decimals: 20,
+        },
+        ..ChecksumOptions::default()
+    };
+    let (_, warnings) = checksum_input_with_warnings(level3_data, &opts);
+    assert!(!warnings.is_empty());
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.field == "qty" && warning.residual == f64::INFINITY));
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str], file: &std::path::Path) -> (Output, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_level3bug"))
+        .args(args)
+        .arg("--file")
+        .arg(file)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+    (output, stdout)
+}
+
+#[test]
+fn symbol_errors_are_reported_not_panicked() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut message: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let mut eth = message["data"][0].clone();
+    eth["symbol"] = "ETH/USD".into();
+    message["data"].as_array_mut().unwrap().push(eth);
+    let file = std::env::temp_dir().join("level3bug-cli-two-symbols.json");
+    std::fs::write(&file, message.to_string()).unwrap();
+
+    let (output, stdout) = run(&["--reference"], &file);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert_eq!(stdout, "ERROR: snapshot contains 2 symbols, pick one\n");
+    assert!(output.stderr.is_empty());
+
+    let (output, stdout) = run(&["--reference", "--symbol", "XRP/USD"], &file);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert_eq!(stdout, "ERROR: symbol XRP/USD not found in snapshot\n");
+
+    let (output, stdout) = run(&["--reference", "--symbol", "BTC/USD"], &file);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.ends_with("Checksum OK!\n"));
+}
+
+#[test]
+fn round_float_warns_and_strict_float_is_its_alias() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+    let file = std::env::temp_dir().join("level3bug-cli-round-float.json");
+    std::fs::write(&file, line_str.replacen("\"44939.5\"", "\"44939.56\"", 1)).unwrap();
+
+    // exact by default, the price beyond the precision is an error
+    let (output, stdout) = run(&["--reference"], &file);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert!(
+        stdout.contains("ERROR: price 44939.56 of order"),
+        "{}",
+        stdout
+    );
+
+    for flag in ["--round-float", "--strict-float"] {
+        let (_, stdout) = run(&["--reference", flag], &file);
+        assert!(
+            stdout.contains("WARNING: price 44939.56 of order"),
+            "{}",
+            stdout
+        );
+        assert!(
+            stdout.ends_with("ERROR: Checksum mismatch!\n"),
+            "{}",
+            stdout
+        );
+    }
+}
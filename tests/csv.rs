@@ -0,0 +1,55 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(feature = "csv")]
+
+use level3bug::{from_file, to_csv, Order, OrderEvent};
+use time::OffsetDateTime;
+
+#[test]
+fn csv_rows_asks_then_bids() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    level3_data.bids.push(
+        Order::builder()
+            .id("B1")
+            .price(44900.5)
+            .qty(0.001)
+            .timestamp(OffsetDateTime::from_unix_timestamp(1_718_625_600).unwrap())
+            .event(OrderEvent::Add)
+            .build(),
+    );
+
+    let mut csv = Vec::new();
+    to_csv(&level3_data, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 1 + level3_data.iter().len());
+    assert_eq!(rows[0], "side,order_id,price,qty,timestamp,event");
+
+    let first_ask = &level3_data.asks[0];
+    assert!(rows[1].starts_with(&format!("ask,{},", first_ask.order_id)));
+    assert!(rows[1].ends_with("Z,"));
+    assert!(rows[1 + level3_data.asks.len()].starts_with("bid,"));
+    assert_eq!(
+        *rows.last().unwrap(),
+        "bid,B1,44900.5,0.001,2024-06-17T12:00:00Z,add"
+    );
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{derive_updates, diff, from_file, Order, OrderBook, Side};
+use time::Duration;
+
+#[test]
+fn derived_updates_turn_old_into_new() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let old = &from_file(path).unwrap()[0];
+    let newest = old
+        .bids
+        .iter()
+        .chain(old.asks.iter())
+        .map(|order| order.timestamp)
+        .max()
+        .unwrap();
+
+    let mut new = old.clone();
+    // deletes
+    new.bids.remove(3);
+    new.asks.remove(0);
+    // qty modify in place
+    new.bids[0].order_qty = 0.5;
+    // price modify, moves behind the resting orders of a deeper level
+    let mut moved = new.asks.remove(1);
+    moved.limit_price = new.asks[5].limit_price;
+    moved.timestamp = newest + Duration::seconds(1);
+    let level_end = new
+        .asks
+        .iter()
+        .rposition(|ask| ask.limit_price == moved.limit_price)
+        .unwrap();
+    new.asks.insert(level_end + 1, moved);
+    // adds at the best bid level and at a new best ask level
+    let add = |order_id: &str, price: f64| {
+        Order::builder()
+            .id(order_id)
+            .price(price)
+            .qty(0.25)
+            .timestamp(newest + Duration::seconds(2))
+            .build()
+    };
+    let best_bid_end = new
+        .bids
+        .iter()
+        .rposition(|bid| bid.limit_price == new.bids[0].limit_price)
+        .unwrap();
+    new.bids
+        .insert(best_bid_end + 1, add("NEW-BID", new.bids[0].limit_price));
+    new.asks
+        .insert(0, add("NEW-ASK", new.asks[0].limit_price - 0.1));
+
+    let updates = derive_updates(old, &new);
+    assert_eq!(diff(old, &new).modified.len(), 2);
+    let mut book = OrderBook::from_snapshot(old);
+    for (side, order) in &updates {
+        book.apply(*side, order).unwrap();
+    }
+    assert_eq!(book.bids(), new.bids.as_slice());
+    assert_eq!(book.asks(), new.asks.as_slice());
+    assert!(derive_updates(&new, &new).is_empty());
+    assert!(updates.iter().any(|(side, _)| *side == Side::Ask));
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{Level3Data, Order, Precision};
+use time::{OffsetDateTime, UtcOffset};
+
+fn order(price: f64, qty: f64) -> Order {
+    Order::builder()
+        .id("O1")
+        .price(price)
+        .qty(qty)
+        .timestamp(OffsetDateTime::from_unix_timestamp(1_718_625_600).unwrap())
+        .build()
+}
+
+#[test]
+fn order_format_follows_precision() {
+    let order = order(3512.25, 1.5);
+    assert_eq!(
+        format!("{:?}", order),
+        "O1:   1.50000000 @ 3512.2  1718625600.000000"
+    );
+    let precision = Precision {
+        price_decimals: 2,
+        qty_decimals: 4,
+    };
+    assert_eq!(
+        order.format_with(precision),
+        "O1:   1.5000 @ 3512.25  1718625600.000000"
+    );
+}
+
+#[test]
+fn ladder_uses_symbol_precision() {
+    let level3_data = Level3Data {
+        symbol: "ETH/USD".to_string(),
+        bids: vec![order(3512.25, 1.5)],
+        asks: vec![order(3512.75, 0.25)],
+        checksum: 0,
+    };
+    let ladder = level3_data.to_string();
+    let top = ladder.lines().nth(2).unwrap();
+    assert_eq!(
+        top,
+        "   1     1.50000000    3512.25 | 3512.75    0.25000000"
+    );
+}
+
+#[test]
+fn timestamps_at_offset() {
+    let order = order(3512.25, 1.5);
+    let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+    assert_eq!(
+        order.format_at(Precision::default(), offset),
+        "O1:   1.50000000 @ 3512.2  2024-06-17T14:00:00+02:00"
+    );
+
+    let level3_data = Level3Data {
+        symbol: "ETH/USD".to_string(),
+        bids: vec![order.clone()],
+        asks: vec![],
+        checksum: 0,
+    };
+    let header = |ladder: String| ladder.lines().next().unwrap().to_string();
+    assert_eq!(
+        header(level3_data.to_string()),
+        "ETH/USD (checksum 0, 2024-06-17T12:00:00Z)"
+    );
+    assert_eq!(
+        header(level3_data.render_ladder_at(10, offset)),
+        "ETH/USD (checksum 0, 2024-06-17T14:00:00+02:00)"
+    );
+}
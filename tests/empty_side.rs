@@ -0,0 +1,77 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    checksum_input_string, compute_checksum, from_file, imbalance, is_crossed, mid_price, spread,
+    stats, to_level2, top_orders, validate, Level3Data, OrderBook, Side,
+};
+
+fn bids_only() -> Level3Data {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    level3_data.asks.clear();
+    level3_data
+}
+
+#[test]
+fn one_sided_book_accessors() {
+    let level3_data = bids_only();
+
+    assert!(level3_data.best_bid().is_some());
+    assert_eq!(level3_data.best_ask(), None);
+    assert_eq!(spread(&level3_data), None);
+    assert_eq!(mid_price(&level3_data), None);
+    assert!(!is_crossed(&level3_data));
+    assert!(validate(&level3_data).is_ok());
+    assert_eq!(imbalance(&level3_data, 10), 1.0);
+    assert!(to_level2(&level3_data, Side::Ask).is_empty());
+    assert_eq!(stats(&level3_data).ask_levels, 0);
+    assert!(top_orders(&level3_data, Side::Ask, 5).is_empty());
+    assert_eq!(
+        top_orders(&level3_data, Side::Bid, 2),
+        &level3_data.bids[..2]
+    );
+    assert_eq!(
+        top_orders(&level3_data, Side::Bid, usize::MAX),
+        &level3_data.bids[..]
+    );
+    assert!(level3_data.to_string().lines().count() > 2);
+}
+
+#[test]
+fn one_sided_book_checksum_skips_empty_side() {
+    let level3_data = bids_only();
+    let mut empty = bids_only();
+    empty.bids.clear();
+    assert_eq!(empty.best_bid(), None);
+    assert_eq!(checksum_input_string(&empty), "");
+
+    let full = from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json")).unwrap();
+    let crc_str = checksum_input_string(&level3_data);
+    assert!(checksum_input_string(&full[0]).ends_with(&crc_str));
+    assert_eq!(
+        compute_checksum(&level3_data),
+        crc32fast::hash(crc_str.as_bytes())
+    );
+
+    let book = OrderBook::from_snapshot(&level3_data);
+    assert_eq!(book.best_ask(), None);
+    assert_eq!(book.checksum(), compute_checksum(&level3_data));
+}
@@ -0,0 +1,232 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::path::Path;
+
+use level3bug::{
+    check_timestamps_at, checksum_input_string, from_file, health_check, health_check_with,
+    inferred_depth, load_and_verify, parse_snapshot, validate_depth, verify_snapshot,
+    ChecksumOptions, Level3Data, Level3Error, Order, Side, SNAPSHOT_DEPTHS,
+};
+use time::Duration;
+
+fn snapshot(file: &str) -> Level3Data {
+    from_file(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file))
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn reference_snapshot_is_healthy() {
+    let report = health_check(&snapshot("level3-doc.json"));
+    assert!(report.is_healthy(), "{}", report);
+    assert!(report.ordered && report.unique_ids && report.precision_ok && report.checksum_ok);
+    assert!(!report.crossed && !report.locked);
+}
+
+#[test]
+fn report_collects_every_problem() {
+    let level3_data = snapshot("level3-bug.json");
+    assert!(health_check_with(&level3_data, &ChecksumOptions::level3_bug()).is_healthy());
+
+    let mut level3_data = level3_data;
+    let best_ask = level3_data.asks[0].clone();
+    level3_data.bids.insert(0, best_ask);
+    level3_data.bids[2].limit_price = 70000.0;
+    level3_data.asks[1].order_qty = 0.000000001;
+    let report = health_check_with(&level3_data, &ChecksumOptions::level3_bug());
+    assert!(!report.is_healthy());
+    assert!(report.crossed && report.locked);
+    assert!(!report.ordered && !report.unique_ids && !report.precision_ok && !report.checksum_ok);
+    assert_eq!(report.details.len(), 4, "{}", report);
+    assert!(report
+        .to_string()
+        .starts_with("ordering:   FAILED\ncrossed:    locked\n"));
+}
+
+#[test]
+fn load_and_verify_reports_both_checksums() {
+    let path = |file| format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file);
+    let bug_file = path("level3-bug.json");
+    let report =
+        load_and_verify(Path::new(&bug_file), None, &ChecksumOptions::level3_bug()).unwrap();
+    assert!(report.matches);
+    assert_eq!((report.expected, report.computed), (1724874262, 1724874262));
+    assert_eq!(report.symbol, "BTC/USD");
+
+    let report = load_and_verify(Path::new(&bug_file), None, &ChecksumOptions::default()).unwrap();
+    assert!(!report.matches);
+    assert_eq!(report.expected, 1724874262);
+    assert_ne!(report.computed, report.expected);
+
+    assert!(matches!(
+        load_and_verify(
+            Path::new(&path("missing.json")),
+            None,
+            &ChecksumOptions::default()
+        ),
+        Err(Level3Error::Io(_))
+    ));
+    assert!(matches!(
+        load_and_verify(
+            Path::new(&bug_file),
+            Some("ETH/USD"),
+            &ChecksumOptions::level3_bug()
+        ),
+        Err(Level3Error::UnknownSymbol { symbol }) if symbol == "ETH/USD"
+    ));
+}
+
+#[test]
+fn verify_snapshot_reports_the_crc_input() {
+    let line_str =
+        std::fs::read_to_string(format!("{}/level3-doc.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    let opts = ChecksumOptions::default();
+    let report = verify_snapshot(&line_str, Some("BTC/USD"), &opts).unwrap();
+    assert!(report.matches);
+    assert_eq!(report.computed, 1063832831);
+    let level3_data = parse_snapshot(&line_str).unwrap();
+    assert_eq!(report.crc_input, checksum_input_string(&level3_data[0]));
+
+    // a price beyond the precision fails unless strict_precision is off
+    let line_str = line_str.replacen("\"44939.5\"", "\"44939.56\"", 1);
+    assert!(matches!(
+        verify_snapshot(&line_str, None, &opts),
+        Err(Level3Error::Precision { .. })
+    ));
+    let opts = ChecksumOptions {
+        strict_precision: false,
+        ..ChecksumOptions::default()
+    };
+    assert!(!verify_snapshot(&line_str, None, &opts).unwrap().matches);
+}
+
+#[test]
+fn inferred_depth_is_a_level3_depth() {
+    let mut level3_data = snapshot("level3-doc.json");
+    assert_eq!(inferred_depth(&level3_data), Some(SNAPSHOT_DEPTHS[0]));
+
+    // one level more than the checksum depth needs the next depth the channel offers
+    let mut ask = level3_data.asks.last().unwrap().clone();
+    ask.order_id = "A11".to_string();
+    ask.limit_price += 1.0;
+    level3_data.asks.push(ask);
+    assert_eq!(inferred_depth(&level3_data), Some(100));
+}
+
+// one single order ask level per price
+fn asks_at(prices: impl IntoIterator<Item = f64>) -> Level3Data {
+    Level3Data {
+        symbol: "BTC/USD".to_string(),
+        bids: Vec::new(),
+        asks: prices
+            .into_iter()
+            .enumerate()
+            .map(|(index, price)| {
+                Order::builder()
+                    .id(format!("A{index}"))
+                    .price(price)
+                    .qty(1.0)
+                    .build()
+            })
+            .collect(),
+        checksum: 0,
+    }
+}
+
+#[test]
+fn validate_depth_counts_price_levels() {
+    let mut level3_data = snapshot("level3-doc.json");
+    assert!(validate_depth(&level3_data, 10).is_ok());
+    assert!(matches!(
+        validate_depth(&level3_data, 9),
+        Err(Level3Error::DepthExceeded {
+            side: Side::Bid,
+            levels: 10,
+            depth: 9
+        })
+    ));
+
+    // a second order at the best ask price is no extra level
+    let mut ask = level3_data.asks[0].clone();
+    ask.order_id = "A0b".to_string();
+    level3_data.asks.insert(1, ask);
+    assert!(validate_depth(&level3_data, 10).is_ok());
+
+    // depths the channel doesn't offer are checked the same way
+    let level3_data = asks_at((0..26).map(|index| 100.0 + index as f64));
+    assert!(validate_depth(&level3_data, 26).is_ok());
+    assert!(matches!(
+        validate_depth(&level3_data, 25),
+        Err(Level3Error::DepthExceeded {
+            side: Side::Ask,
+            levels: 26,
+            depth: 25
+        })
+    ));
+    assert_eq!(inferred_depth(&level3_data), Some(100));
+
+    let level3_data = asks_at((0..1001).map(|index| 100.0 + index as f64));
+    assert_eq!(inferred_depth(&level3_data), None);
+    assert!(validate_depth(&level3_data, SNAPSHOT_DEPTHS[2]).is_err());
+}
+
+#[test]
+fn validate_depth_counts_nan_prices_as_levels() {
+    let level3_data = asks_at([f64::NAN; 11]);
+    assert!(validate_depth(&level3_data, 11).is_ok());
+    assert!(matches!(
+        validate_depth(&level3_data, 10),
+        Err(Level3Error::DepthExceeded { levels: 11, .. })
+    ));
+    assert_eq!(inferred_depth(&level3_data), Some(100));
+}
+
+#[test]
+fn timestamps_are_checked_per_order_not_by_order() {
+    let mut level3_data = snapshot("level3-doc.json");
+    let now = level3_data.bids[0].timestamp;
+    for order in level3_data
+        .bids
+        .iter_mut()
+        .chain(level3_data.asks.iter_mut())
+    {
+        order.timestamp = now;
+    }
+
+    // out of order and equal timestamps are fine, only the distance to now counts
+    level3_data.bids[1].timestamp = now - Duration::hours(1);
+    level3_data.bids[2].timestamp = now + Duration::seconds(5);
+    assert!(check_timestamps_at(&level3_data, now).is_ok());
+
+    level3_data.asks[1].timestamp = now + Duration::seconds(6);
+    assert!(matches!(
+        check_timestamps_at(&level3_data, now),
+        Err(Level3Error::FutureTimestamp { order_id, delta })
+            if order_id == level3_data.asks[1].order_id && delta == Duration::seconds(6)
+    ));
+
+    level3_data.asks[1].timestamp = now;
+    level3_data.bids[3].timestamp = now - Duration::days(366);
+    assert!(matches!(
+        check_timestamps_at(&level3_data, now),
+        Err(Level3Error::StaleTimestamp { order_id, .. }) if order_id == level3_data.bids[3].order_id
+    ));
+}
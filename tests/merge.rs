@@ -0,0 +1,57 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{from_file, merge, verify_checksum, Level3Data, Level3Error};
+
+#[test]
+fn paginated_snapshot_merges_back() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = from_file(path).unwrap().remove(0);
+
+    // two pages that overlap by one order per side, the second page lists its asks reversed
+    let mut first = level3_data.clone();
+    first.bids.truncate(6);
+    first.asks.truncate(8);
+    let mut second = level3_data.clone();
+    second.bids.drain(..5);
+    second.asks.drain(..7);
+    second.asks.reverse();
+
+    let merged = merge(&[first, second]).unwrap();
+    assert_eq!(merged, level3_data);
+    assert!(verify_checksum(&merged).is_ok());
+}
+
+#[test]
+fn merge_rejects_other_symbols() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = from_file(path).unwrap().remove(0);
+    let other = Level3Data {
+        symbol: "ETH/USD".to_string(),
+        ..level3_data.clone()
+    };
+
+    assert!(matches!(
+        merge(&[level3_data, other]),
+        Err(Level3Error::SymbolMismatch { expected, found })
+            if expected == "BTC/USD" && found == "ETH/USD"
+    ));
+    assert!(matches!(merge(&[]), Err(Level3Error::EmptyData)));
+}
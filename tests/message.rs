@@ -0,0 +1,110 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    parse, parse_message, parse_message_with, validate_envelope, Level3Error, Message, ParseOptions,
+};
+
+fn snapshot_line() -> String {
+    std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json")).unwrap()
+}
+
+#[test]
+fn envelope_fields_besides_the_data_are_ignored() {
+    let line_str = snapshot_line();
+    let with_sequence = line_str.replacen('{', r#"{"sequence":42,"#, 1);
+    let message = parse_message(&line_str).unwrap();
+    assert!(matches!(message, Message::Snapshot(_)));
+
+    assert_eq!(parse_message(&with_sequence).unwrap(), message);
+    let opts = ParseOptions {
+        max_orders: Some(100),
+    };
+    assert_eq!(parse_message_with(&with_sequence, &opts).unwrap(), message);
+    assert_eq!(parse(&with_sequence).unwrap().sequence, Some(42));
+}
+
+#[test]
+fn validate_envelope_names_the_broken_field() {
+    let mut envelope: serde_json::Value = serde_json::from_str(&snapshot_line()).unwrap();
+    assert!(validate_envelope(&envelope).is_ok());
+
+    for value in [
+        serde_json::json!([]),
+        serde_json::json!("level3"),
+        serde_json::Value::Null,
+    ] {
+        assert!(matches!(
+            validate_envelope(&value),
+            Err(Level3Error::EnvelopeNotObject)
+        ));
+    }
+
+    let cases = [
+        ("channel", "string", None),
+        ("channel", "string", Some(serde_json::json!(3))),
+        ("type", "string", None),
+        ("type", "string", Some(serde_json::json!(["snapshot"]))),
+        ("data", "array", None),
+        ("data", "array", Some(serde_json::json!({}))),
+    ];
+    for (name, expected_type, replacement) in cases {
+        let original = envelope[name].clone();
+        match &replacement {
+            Some(value) => envelope[name] = value.clone(),
+            None => {
+                envelope.as_object_mut().unwrap().remove(name);
+            }
+        }
+        match validate_envelope(&envelope) {
+            Err(Level3Error::InvalidEnvelope { field, expected }) => {
+                assert_eq!(
+                    (field, expected),
+                    (name, expected_type),
+                    "{:?}",
+                    replacement
+                )
+            }
+            other => panic!("{} {:?}: {:?}", name, replacement, other),
+        }
+        envelope[name] = original;
+    }
+}
+
+#[test]
+fn unknown_message_types_are_rejected() {
+    let line_str = snapshot_line();
+    for msg_type in ["bogus", "Snapshot", ""] {
+        let bogus = line_str.replacen(
+            r#""type":"snapshot""#,
+            &format!(r#""type":"{}""#, msg_type),
+            1,
+        );
+        assert!(
+            matches!(parse_message(&bogus), Err(Level3Error::Json(_))),
+            "{}",
+            msg_type
+        );
+        let opts = ParseOptions::default();
+        assert!(parse_message_with(&bogus, &opts).is_err(), "{}", msg_type);
+        // the envelope shape is fine, only the value is unknown
+        assert!(validate_envelope(&serde_json::from_str(&bogus).unwrap()).is_ok());
+    }
+}
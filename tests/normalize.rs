@@ -0,0 +1,50 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{compute_checksum, from_file, normalize, Order};
+
+// Fisher-Yates with a fixed LCG so the test is reproducible
+fn shuffle(orders: &mut [Order], mut seed: u64) {
+    for i in (1..orders.len()).rev() {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let j = (seed >> 33) as usize % (i + 1);
+        orders.swap(i, j);
+    }
+}
+
+#[test]
+fn normalize_restores_checksum_of_shuffled_snapshot() {
+    for (file, seed) in [("level3-doc.json", 1), ("level3-bug.json", 2)] {
+        let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file);
+        let level3_data = &from_file(path).unwrap()[0];
+
+        let mut shuffled = level3_data.clone();
+        shuffle(&mut shuffled.bids, seed);
+        shuffle(&mut shuffled.asks, seed + 1);
+        shuffled.asks.push(shuffled.asks[0].clone());
+        assert_ne!(shuffled.bids, level3_data.bids);
+
+        normalize(&mut shuffled);
+        assert_eq!(&shuffled, level3_data);
+        assert_eq!(compute_checksum(&shuffled), compute_checksum(level3_data));
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::cmp::Ordering;
+
+use level3bug::{cmp_orders, events_by_time, Level3Data, Order, Side};
+use time::{Duration, OffsetDateTime};
+
+fn order(order_id: &str, limit_price: f64, timestamp: OffsetDateTime) -> Order {
+    Order::builder()
+        .id(order_id)
+        .price(limit_price)
+        .qty(1.0)
+        .timestamp(timestamp)
+        .build()
+}
+
+#[test]
+fn events_by_time_breaks_ties_by_order_id() {
+    let start = OffsetDateTime::from_unix_timestamp(1_718_625_600).unwrap();
+    let level3_data = Level3Data {
+        symbol: "BTC/USD".to_string(),
+        bids: vec![
+            order("O3", 100.0, start + Duration::seconds(1)),
+            order("O2", 99.0, start),
+        ],
+        asks: vec![
+            order("O1", 101.0, start + Duration::seconds(1)),
+            order("O0", 102.0, start + Duration::seconds(2)),
+        ],
+        checksum: 0,
+    };
+
+    // O1 and O3 share a timestamp, the ask O1 comes first by its order_id
+    let events: Vec<(Side, &str)> = events_by_time(&level3_data)
+        .into_iter()
+        .map(|(side, order)| (side, order.order_id.as_str()))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (Side::Bid, "O2"),
+            (Side::Ask, "O1"),
+            (Side::Bid, "O3"),
+            (Side::Ask, "O0"),
+        ]
+    );
+}
+
+#[test]
+fn cmp_orders_sorts_the_price_by_side() {
+    let start = OffsetDateTime::from_unix_timestamp(1_718_625_600).unwrap();
+    let low = order("O1", 100.0, start);
+    let high = order("O2", 101.0, start);
+    assert_eq!(cmp_orders(Side::Bid, &high, &low), Ordering::Less);
+    assert_eq!(cmp_orders(Side::Ask, &high, &low), Ordering::Greater);
+    assert_eq!(cmp_orders(Side::Ask, &low, &high), Ordering::Less);
+
+    // at an equal price both sides queue the older order first, then the lower order_id
+    let newer = order("O0", 100.0, start + Duration::seconds(1));
+    let same_time = order("O3", 100.0, start);
+    for side in [Side::Bid, Side::Ask] {
+        assert_eq!(cmp_orders(side, &low, &newer), Ordering::Less);
+        assert_eq!(cmp_orders(side, &newer, &low), Ordering::Greater);
+        assert_eq!(cmp_orders(side, &low, &same_time), Ordering::Less);
+        assert_eq!(cmp_orders(side, &low, &low), Ordering::Equal);
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{Order, OrderEvent};
+use proptest::prelude::*;
+use time::OffsetDateTime;
+
+fn order_event() -> impl Strategy<Value = Option<OrderEvent>> {
+    prop_oneof![
+        Just(None),
+        Just(Some(OrderEvent::Add)),
+        Just(Some(OrderEvent::Modify)),
+        Just(Some(OrderEvent::Delete)),
+        Just(Some(OrderEvent::Snapshot)),
+    ]
+}
+
+fn timestamp() -> impl Strategy<Value = OffsetDateTime> {
+    // years 1970..=9999 with nanosecond precision, the range rfc3339 can represent
+    (0i128..253_402_300_799_999_999_999i128)
+        .prop_map(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).unwrap())
+}
+
+fn order() -> impl Strategy<Value = Order> {
+    (
+        order_event(),
+        any::<String>(),
+        prop::num::f64::NORMAL | prop::num::f64::ZERO | prop::num::f64::SUBNORMAL,
+        prop::num::f64::NORMAL | prop::num::f64::ZERO | prop::num::f64::SUBNORMAL,
+        timestamp(),
+    )
+        .prop_map(
+            |(event, order_id, limit_price, order_qty, timestamp)| Order {
+                event,
+                order_id,
+                limit_price,
+                order_qty,
+                timestamp,
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn order_json_round_trip(order in order()) {
+        let json = serde_json::to_string(&order).unwrap();
+        let parsed: Order = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(&parsed, &order);
+        prop_assert_eq!(parsed.limit_price.to_bits(), order.limit_price.to_bits());
+        prop_assert_eq!(parsed.order_qty.to_bits(), order.order_qty.to_bits());
+        prop_assert_eq!(json.contains("\"event\""), order.event.is_some());
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    checksum_input_string, checksum_matches, checksum_of, checksum_orders, compute_checksum,
+    from_file, order_checksum_contribution, try_checksum_input_string, try_checksum_with_levels,
+    try_compute_checksum, verify_against, verify_checksum, ChecksumBuilder, ChecksumOptions,
+    Level3Error, Side,
+};
+
+// CRC input for level3-doc.json, the example from the kraken api docs
+const REF_STR: &str = "44939545230839344939511126144939510000044939510000004495001033492644953064537449550250000449596356300004495963563000044960133807244960288967575449670314392283449785677896044979235630000449394889686994493944521000044939410000000449394142963234493942500000044939410292988449394338800004493941281408604493713346877449347356300004493022273429944930210000004493025550000449302700000004493021500000044928010524044919633870000449195761000044912035630000449097669000044901988982";
+
+#[test]
+fn reference_snapshot_checksum() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    assert_eq!(checksum_input_string(level3_data), REF_STR);
+    assert_eq!(compute_checksum(level3_data), 1063832831);
+    assert_eq!(compute_checksum(level3_data), level3_data.checksum);
+    assert_eq!(
+        checksum_of(
+            &level3_data.asks,
+            &level3_data.bids,
+            &ChecksumOptions::default()
+        ),
+        1063832831
+    );
+    assert!(checksum_matches(level3_data));
+    assert!(verify_checksum(level3_data).is_ok());
+    assert!(verify_against(level3_data, 1063832831).is_ok());
+    assert!(matches!(
+        verify_against(level3_data, 1),
+        Err(Level3Error::ChecksumMismatch { expected: 1, .. })
+    ));
+}
+
+#[test]
+fn bug_snapshot_checksum_with_level_quirk() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+    let opts = ChecksumOptions::level3_bug();
+
+    let crc_str = try_checksum_input_string(level3_data, &opts).unwrap();
+    assert_eq!(crc32fast::hash(crc_str.as_bytes()), level3_data.checksum);
+    assert_eq!(
+        try_compute_checksum(level3_data, &opts).unwrap(),
+        1724874262
+    );
+    let quirk = |side, level| match side {
+        Side::Ask => level <= 12 && level != 10 && level != 11,
+        Side::Bid => level <= 10,
+    };
+    assert_eq!(
+        try_checksum_with_levels(level3_data, &ChecksumOptions::default(), quirk).unwrap(),
+        1724874262
+    );
+    let opts = ChecksumOptions::default();
+    let top_10 = |side, level| opts.includes_level(side, level);
+    assert_eq!(
+        try_checksum_with_levels(level3_data, &opts, top_10).unwrap(),
+        compute_checksum(level3_data)
+    );
+    assert_ne!(compute_checksum(level3_data), level3_data.checksum);
+    assert!(!checksum_matches(level3_data));
+    assert!(matches!(
+        verify_checksum(level3_data),
+        Err(Level3Error::ChecksumMismatch {
+            expected: 1724874262,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn order_contribution_within_depth() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    let contribution = |order_id| order_checksum_contribution(level3_data, order_id);
+    assert_eq!(
+        contribution("OTDJNX-HEJBI-YTF6BJ").as_deref(),
+        Some("6556835000000")
+    );
+    // the 10th ask level is the last one of the default depth, the 12th is beyond it
+    assert_eq!(
+        contribution("OFZ3F2-PAONC-URMCAS").as_deref(),
+        Some("65594045739377")
+    );
+    assert_eq!(contribution("OE272J-J2UQX-WWD2JI"), None);
+    assert_eq!(contribution("unknown"), None);
+}
+
+#[test]
+fn orders_fed_into_checksum() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    let (asks, bids) = checksum_orders(level3_data, &ChecksumOptions::default());
+    let crc_str: String = asks
+        .iter()
+        .chain(&bids)
+        .map(|order| order_checksum_contribution(level3_data, &order.order_id).unwrap())
+        .collect();
+    assert_eq!(crc_str, checksum_input_string(level3_data));
+    assert!(asks
+        .iter()
+        .any(|order| order.order_id == "OFZ3F2-PAONC-URMCAS"));
+    assert!(!asks
+        .iter()
+        .any(|order| order.order_id == "OE272J-J2UQX-WWD2JI"));
+
+    let opts = ChecksumOptions::level3_bug();
+    let (asks, _) = checksum_orders(level3_data, &opts);
+    assert!(!asks
+        .iter()
+        .any(|order| order.order_id == "OFZ3F2-PAONC-URMCAS"));
+    assert!(asks
+        .iter()
+        .any(|order| order.order_id == "OE272J-J2UQX-WWD2JI"));
+}
+
+#[test]
+fn pushed_orders_checksum() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    let mut builder = ChecksumBuilder::new(ChecksumOptions::level3_bug());
+    level3_data
+        .asks
+        .iter()
+        .for_each(|order| builder.push_ask(order));
+    level3_data
+        .bids
+        .iter()
+        .for_each(|order| builder.push_bid(order));
+    assert_eq!(builder.finish(), 1724874262);
+
+    // the sides are counted apart, interleaving them doesn't change the checksum
+    let mut builder = ChecksumBuilder::new(ChecksumOptions::default());
+    for (ask, bid) in level3_data.asks.iter().zip(&level3_data.bids) {
+        builder.push_bid(bid);
+        builder.push_ask(ask);
+    }
+    assert_eq!(builder.try_finish().unwrap(), compute_checksum(level3_data));
+}
+
+#[test]
+fn iterate_asks_then_bids() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    let orders: Vec<_> = level3_data.into_iter().collect();
+    assert_eq!(
+        orders.len(),
+        level3_data.asks.len() + level3_data.bids.len()
+    );
+    assert_eq!(level3_data.iter().len(), orders.len());
+    assert_eq!(orders[0], (Side::Ask, &level3_data.asks[0]));
+    assert_eq!(
+        orders[level3_data.asks.len()],
+        (Side::Bid, &level3_data.bids[0])
+    );
+    let mut sides = Vec::new();
+    for (side, _) in level3_data {
+        if sides.last() != Some(&side) {
+            sides.push(side);
+        }
+    }
+    assert_eq!(sides, [Side::Ask, Side::Bid]);
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::process::Command;
+
+use level3bug::{apply_updates, parse_ndjson, Level3Error, Message, OrderBook};
+
+// a snapshot with 12 ask levels, an update with a matching checksum and one whose checksum is
+// off by one
+const CAPTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/replay.ndjson");
+
+fn messages() -> Vec<Message> {
+    parse_ndjson(BufReader::new(File::open(CAPTURE).unwrap()))
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn parse_ndjson_reads_every_line() {
+    let messages = messages();
+    assert_eq!(messages.len(), 3);
+    assert!(matches!(messages[0], Message::Snapshot(_)));
+    assert!(matches!(messages[1], Message::Update(_)));
+
+    let lines = "\n{\"channel\":\"level3\"}\n\n".as_bytes();
+    let parsed: Vec<_> = parse_ndjson(lines).collect();
+    assert_eq!(parsed.len(), 1);
+    assert!(matches!(parsed[0], Err(Level3Error::Json(_))));
+}
+
+#[test]
+fn apply_updates_stops_at_corrupt_update() {
+    let messages = messages();
+    let mut book = OrderBook::from_snapshot(&messages[0].data()[0]);
+    apply_updates(&mut book, &messages[..2]).unwrap();
+    assert_eq!(book.checksum(), messages[1].data()[0].checksum);
+
+    let mut book = OrderBook::from_snapshot(&messages[0].data()[0]);
+    assert!(matches!(
+        apply_updates(&mut book, &messages),
+        Err(Level3Error::UpdateChecksumMismatch {
+            index: 2,
+            expected: 2739947242,
+            computed: 2739947243,
+        })
+    ));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn replay_exits_with_mismatch() {
+    let output = Command::new(env!("CARGO_BIN_EXE_level3bug"))
+        .args(["replay", CAPTURE])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert!(stdout.starts_with("ERROR: checksum mismatch at message 2 (line 3):"));
+
+    // with the quirk of level3-bug.json ask levels 10 and 11 are skipped, the snapshot fails
+    let output = Command::new(env!("CARGO_BIN_EXE_level3bug"))
+        .args(["replay", "--ask-level-quirk", CAPTURE])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert!(stdout.starts_with("ERROR: checksum mismatch at message 0 (line 1):"));
+
+    let good = std::env::temp_dir().join("level3bug-replay-good.ndjson");
+    let capture = std::fs::read_to_string(CAPTURE).unwrap();
+    let lines: Vec<&str> = capture.lines().take(2).collect();
+    std::fs::write(&good, lines.join("\n")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_level3bug"))
+        .arg("replay")
+        .arg(&good)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Replayed 2 messages, all checksums OK!\n"
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn replay_reports_errors_with_the_line() {
+    let capture = std::fs::read_to_string(CAPTURE).unwrap();
+    let lines: Vec<&str> = capture.lines().collect();
+    let replay = |name: &str, content: String| {
+        let file = std::env::temp_dir().join(name);
+        std::fs::write(&file, content).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_level3bug"))
+            .arg("replay")
+            .arg(&file)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stderr.is_empty());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // the blank line counts for the line number, not for the message index
+    let stdout = replay(
+        "level3bug-replay-update-first.ndjson",
+        format!("\n{}\n", lines[1]),
+    );
+    assert_eq!(
+        stdout,
+        "ERROR: message 0 (line 2): update before the first snapshot\n"
+    );
+
+    // a price beyond the precision is an error of the checksum, not a panic
+    let stdout = replay(
+        "level3bug-replay-precision.ndjson",
+        lines[0].replacen("\"limit_price\":100.0", "\"limit_price\":100.01", 1),
+    );
+    assert!(
+        stdout.starts_with("ERROR: message 0 (line 1): price 100.01 of order B1"),
+        "{}",
+        stdout
+    );
+}
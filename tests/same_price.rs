@@ -0,0 +1,222 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    checksum_input_string, explain_checksum, from_file, orders_at_price, price_levels, truncate,
+    try_checksum_input_string, try_explain_checksum, ChecksumOptions, Level3Data, Order, Precision,
+    Side,
+};
+use time::{Duration, OffsetDateTime};
+
+fn level_sizes(
+    level3_data: &Level3Data,
+    opts: &ChecksumOptions,
+    side: Side,
+) -> Vec<(usize, usize)> {
+    let mut sizes: Vec<(usize, usize)> = Vec::new();
+    for entry in try_explain_checksum(level3_data, opts).unwrap() {
+        if entry.side != side {
+            continue;
+        }
+        match sizes.last_mut() {
+            Some((level, count)) if *level == entry.level => *count += 1,
+            _ => sizes.push((entry.level, 1)),
+        }
+    }
+    sizes
+}
+
+#[test]
+fn bug_snapshot_multi_order_level_counts_once() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    // the three orders at 65590.5 form the sixth ask level, levels 10 and 11 are single orders
+    assert_eq!(
+        level_sizes(level3_data, &ChecksumOptions::level3_bug(), Side::Ask),
+        [
+            (0, 1),
+            (1, 1),
+            (2, 1),
+            (3, 1),
+            (4, 1),
+            (5, 3),
+            (6, 1),
+            (7, 1),
+            (8, 1),
+            (11, 1),
+        ]
+    );
+}
+
+#[test]
+fn same_price_orders_out_of_time_order_stay_one_level() {
+    let start = OffsetDateTime::from_unix_timestamp(1_718_625_600).unwrap();
+    let ask = |order_id: &str, price: f64, seconds: i64| -> Order {
+        Order::builder()
+            .id(order_id)
+            .price(price)
+            .qty(1.0)
+            .timestamp(start + Duration::seconds(seconds))
+            .build()
+    };
+    // the first level has a newer order in front of an older one, followed by ten more levels
+    let mut asks = vec![
+        ask("A1", 100.0, 2),
+        ask("A2", 100.0, 0),
+        ask("A3", 100.0, 1),
+    ];
+    for level in 1..=10 {
+        let price = 100.0 + level as f64;
+        asks.push(ask(&format!("L{}", level), price, 0));
+    }
+    let level3_data = Level3Data {
+        symbol: "BTC/USD".to_string(),
+        bids: Vec::new(),
+        asks,
+        checksum: 0,
+    };
+
+    let sizes = level_sizes(&level3_data, &ChecksumOptions::default(), Side::Ask);
+    assert_eq!(sizes.len(), 10);
+    assert_eq!(sizes[0], (0, 3));
+    assert_eq!(sizes[9], (9, 1));
+
+    // the orders of the first level are hashed in feed order, the 11th level is left out
+    let entries = explain_checksum(&level3_data);
+    assert_eq!(entries.len(), 12);
+    let crc_str = checksum_input_string(&level3_data);
+    assert!(crc_str.starts_with(&"1000100000000".repeat(3)));
+    assert!(crc_str.ends_with("1090100000000"));
+}
+
+#[test]
+fn prices_a_bit_apart_share_a_level() {
+    let price = 449.39f64;
+    let next_price = f64::from_bits(price.to_bits() + 1);
+    assert_ne!(price, next_price);
+    let asks = [price, next_price, 449.40]
+        .iter()
+        .enumerate()
+        .map(|(index, price)| {
+            Order::builder()
+                .id(format!("A{}", index))
+                .price(*price)
+                .qty(1.0)
+                .build()
+        })
+        .collect();
+    let level3_data = Level3Data {
+        symbol: "ETH/USD".to_string(),
+        bids: Vec::new(),
+        asks,
+        checksum: 0,
+    };
+    let opts = ChecksumOptions {
+        precision: Precision {
+            price_decimals: 2,
+            qty_decimals: 8,
+        },
+        depth: 2,
+        ..ChecksumOptions::default()
+    };
+
+    let sizes = level_sizes(&level3_data, &opts, Side::Ask);
+    assert_eq!(sizes, [(0, 2), (1, 1)]);
+    assert_eq!(
+        try_checksum_input_string(&level3_data, &opts).unwrap(),
+        "44939100000000".repeat(2) + "44940100000000"
+    );
+
+    // compared bit for bit the second order opens a level of its own and pushes 449.40 out
+    let exact = ChecksumOptions {
+        level_tolerance: false,
+        ..opts
+    };
+    assert_eq!(
+        level_sizes(&level3_data, &exact, Side::Ask),
+        [(0, 1), (1, 1)]
+    );
+
+    // truncate and price_levels group the levels like the checksum
+    let order_counts: Vec<usize> = price_levels(&level3_data, Side::Ask)
+        .map(|level| level.order_count)
+        .collect();
+    assert_eq!(order_counts, [2, 1]);
+    let mut truncated = level3_data.clone();
+    truncate(&mut truncated, 1);
+    assert_eq!(truncated.asks, level3_data.asks[..2]);
+}
+
+#[test]
+fn orders_at_price_uses_symbol_precision() {
+    let bids = [("B1", 3512.24), ("B2", 3512.21), ("B3", 3512.24)]
+        .iter()
+        .map(|(order_id, price)| {
+            Order::builder()
+                .id(*order_id)
+                .price(*price)
+                .qty(1.0)
+                .build()
+        })
+        .collect();
+    // ETH/USD has two price decimals, at the default single decimal both prices are 3512.2
+    let level3_data = Level3Data {
+        symbol: "ETH/USD".to_string(),
+        bids,
+        asks: Vec::new(),
+        checksum: 0,
+    };
+
+    let ids = |price| -> Vec<String> {
+        orders_at_price(&level3_data, Side::Bid, price)
+            .iter()
+            .map(|order| order.order_id.clone())
+            .collect()
+    };
+    assert_eq!(ids(3512.24), ["B1", "B3"]);
+    assert_eq!(ids(3512.21), ["B2"]);
+}
+
+#[test]
+fn price_levels_advance_past_non_finite_prices() {
+    let asks = [f64::NAN, f64::NAN, f64::INFINITY, 100.0]
+        .iter()
+        .enumerate()
+        .map(|(index, price)| {
+            Order::builder()
+                .id(format!("A{}", index))
+                .price(*price)
+                .qty(1.0)
+                .build()
+        })
+        .collect();
+    let level3_data = Level3Data {
+        symbol: "BTC/USD".to_string(),
+        bids: Vec::new(),
+        asks,
+        checksum: 0,
+    };
+    // NaN doesn't equal itself, so either NaN order is a level of its own
+    let order_counts: Vec<usize> = price_levels(&level3_data, Side::Ask)
+        .map(|level| level.order_count)
+        .collect();
+    assert_eq!(order_counts, [1, 1, 1, 1]);
+}
@@ -0,0 +1,222 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    extract_data, from_file, from_reader_with, parse_message, parse_message_with, parse_ndjson,
+    parse_ndjson_with, parse_snapshot, parse_snapshot_with, parse_symbol, to_json, Level3Data,
+    Level3Error, Order, OrderEvent, ParseOptions,
+};
+
+#[test]
+fn level3_data_json_round_trip() {
+    // the bug capture has numeric prices, the api docs example has string prices
+    for file in ["level3-bug.json", "level3-doc.json"] {
+        let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file);
+        let level3_data = from_file(path).unwrap();
+        let level3_data = &level3_data[0];
+
+        let json = to_json(level3_data).unwrap();
+        let parsed: Level3Data = serde_json::from_str(&json).unwrap();
+        assert_eq!(&parsed, level3_data);
+    }
+}
+
+#[test]
+fn extract_data_takes_the_data_array() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let value: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(extract_data(value).unwrap(), from_file(path).unwrap());
+
+    for value in [
+        serde_json::json!({"channel": "level3", "type": "snapshot"}),
+        serde_json::json!({"channel": "level3", "type": "snapshot", "data": {}}),
+        serde_json::json!([]),
+    ] {
+        assert!(matches!(
+            extract_data(value),
+            Err(Level3Error::InvalidEnvelope { field: "data", .. })
+        ));
+    }
+    assert!(matches!(
+        extract_data(serde_json::json!({"data": []})),
+        Err(Level3Error::EmptyData)
+    ));
+}
+
+#[test]
+fn timestamps_as_rfc3339_or_unix_nanos() {
+    let rfc3339 = r#"{"order_id":"O1","limit_price":65568.3,"order_qty":0.05,"timestamp":"2024-06-17T13:13:03.493946565Z"}"#;
+    let nanos = r#"{"order_id":"O1","limit_price":65568.3,"order_qty":0.05,"timestamp":1718629983493946565}"#;
+    let from_rfc3339: Order = serde_json::from_str(rfc3339).unwrap();
+    let from_nanos: Order = serde_json::from_str(nanos).unwrap();
+    assert_eq!(from_rfc3339, from_nanos);
+    assert_eq!(
+        from_nanos.timestamp.unix_timestamp_nanos(),
+        1718629983493946565
+    );
+
+    // both encodings are written back as rfc3339
+    assert_eq!(serde_json::to_string(&from_nanos).unwrap(), rfc3339);
+    let nanos_again = nanos.replace(
+        "1718629983493946565",
+        &from_rfc3339.timestamp.unix_timestamp_nanos().to_string(),
+    );
+    assert_eq!(
+        serde_json::from_str::<Order>(&nanos_again).unwrap(),
+        from_rfc3339
+    );
+
+    let invalid = nanos.replace("1718629983493946565", "\"yesterday\"");
+    assert!(serde_json::from_str::<Order>(&invalid).is_err());
+}
+
+#[test]
+fn max_orders_caps_the_parsed_book() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+    let level3_data = parse_snapshot(&line_str).unwrap();
+    let order_count = level3_data[0].iter().len();
+
+    assert_eq!(
+        parse_snapshot_with(&line_str, &ParseOptions::default()).unwrap(),
+        level3_data
+    );
+    let opts = ParseOptions {
+        max_orders: Some(order_count),
+    };
+    assert_eq!(parse_snapshot_with(&line_str, &opts).unwrap(), level3_data);
+    assert_eq!(
+        from_reader_with(line_str.as_bytes(), &opts).unwrap(),
+        level3_data
+    );
+
+    let opts = ParseOptions {
+        max_orders: Some(order_count - 1),
+    };
+    assert!(matches!(
+        parse_snapshot_with(&line_str, &opts),
+        Err(Level3Error::TooLarge { max_orders }) if max_orders == order_count - 1
+    ));
+    assert!(matches!(
+        from_reader_with(line_str.as_bytes(), &opts),
+        Err(Level3Error::TooLarge { .. })
+    ));
+    // other errors are reported as before
+    assert!(matches!(
+        parse_snapshot_with(r#"{"data":[]}"#, &opts),
+        Err(Level3Error::EmptyData)
+    ));
+    assert!(matches!(
+        parse_snapshot_with(r#"{"data":[{"symbol":"BTC/USD"}]}"#, &opts),
+        Err(Level3Error::Json(_))
+    ));
+    // an unknown field is reported like the derive does, with the fields of `Level3Data`
+    let unknown = r#"{"data":[{"symbol":"BTC/USD","price":1}]}"#;
+    assert_eq!(
+        parse_snapshot_with(unknown, &opts).unwrap_err().to_string(),
+        parse_snapshot(unknown).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn parse_message_with_limits_every_message() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+    let message = parse_message(&line_str).unwrap();
+    let order_count = message.data()[0].iter().len();
+
+    let opts = ParseOptions {
+        max_orders: Some(order_count),
+    };
+    assert_eq!(parse_message_with(&line_str, &opts).unwrap(), message);
+    let wrong_channel = line_str.replace(r#""channel":"level3""#, r#""channel":"book""#);
+    // the same check as `parse_message`, which doesn't know the position of the channel
+    assert!(parse_message_with(&wrong_channel, &opts)
+        .unwrap_err()
+        .to_string()
+        .starts_with(&parse_message(&wrong_channel).unwrap_err().to_string()));
+
+    let opts = ParseOptions {
+        max_orders: Some(order_count - 1),
+    };
+    assert!(matches!(
+        parse_message_with(&line_str, &opts),
+        Err(Level3Error::TooLarge { .. })
+    ));
+    let capture = format!("{}\n\n{}\n", line_str, line_str);
+    let parsed: Vec<_> = parse_ndjson_with(capture.as_bytes(), &opts).collect();
+    assert_eq!(parsed.len(), 2);
+    assert!(parsed
+        .iter()
+        .all(|message| matches!(message, Err(Level3Error::TooLarge { .. }))));
+    let opts = ParseOptions::default();
+    assert_eq!(
+        parse_ndjson_with(capture.as_bytes(), &opts)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>(),
+        parse_ndjson(capture.as_bytes())
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn parse_symbol_builds_one_entry() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let line_str = std::fs::read_to_string(path).unwrap();
+    let btc_usd = parse_snapshot(&line_str).unwrap().remove(0);
+    let mut message: serde_json::Value = serde_json::from_str(&line_str).unwrap();
+    // an entry with orders that don't parse, it must not be built
+    let eth_usd = serde_json::json!({
+        "symbol": "ETH/USD",
+        "bids": [{"order_id": "B1", "limit_price": "not a price"}],
+        "asks": [],
+        "checksum": 0
+    });
+    message["data"].as_array_mut().unwrap().insert(0, eth_usd);
+    let line_str = message.to_string();
+
+    assert_eq!(parse_symbol(&line_str, "BTC/USD").unwrap(), Some(btc_usd));
+    assert_eq!(parse_symbol(&line_str, "XBT/EUR").unwrap(), None);
+    assert!(matches!(
+        parse_symbol(&line_str, "ETH/USD"),
+        Err(Level3Error::Json(_))
+    ));
+    assert!(matches!(
+        parse_symbol(r#"{"data":[]}"#, "BTC/USD"),
+        Err(Level3Error::EmptyData)
+    ));
+}
+
+#[test]
+fn order_event_names_and_codes() {
+    let event = |value: &str| {
+        let json = format!(
+            r#"{{"event":{},"order_id":"O1","limit_price":1.0,"order_qty":1.0,"timestamp":"2024-06-17T12:00:00Z"}}"#,
+            value
+        );
+        serde_json::from_str::<Order>(&json).map(|order| order.event)
+    };
+    assert_eq!(event("2").unwrap(), Some(OrderEvent::Delete));
+    assert_eq!(event(r#""snapshot""#).unwrap(), Some(OrderEvent::Snapshot));
+    // the snapshot marker has no integer code
+    assert!(event("3").is_err());
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::fs::File;
+
+use level3bug::{
+    from_file, stream_checksums, try_compute_checksum, verify_stream, ChecksumOptions, Level3Error,
+};
+
+fn open(file: &str) -> File {
+    File::open(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file)).unwrap()
+}
+
+#[test]
+fn streamed_checksum_matches_full_parse() {
+    for (file, opts) in [
+        ("level3-doc.json", ChecksumOptions::default()),
+        ("level3-bug.json", ChecksumOptions::level3_bug()),
+    ] {
+        let level3_data = &from_file(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file)).unwrap();
+        let checksums = stream_checksums(open(file), &opts).unwrap();
+        assert_eq!(checksums.len(), 1);
+        assert_eq!(checksums[0].symbol, level3_data[0].symbol);
+        assert_eq!(checksums[0].expected, level3_data[0].checksum);
+        assert_eq!(
+            checksums[0].computed,
+            try_compute_checksum(&level3_data[0], &opts).unwrap()
+        );
+        assert!(verify_stream(open(file), &opts).is_ok());
+    }
+
+    assert!(matches!(
+        verify_stream(open("level3-bug.json"), &ChecksumOptions::default()),
+        Err(Level3Error::ChecksumMismatch {
+            expected: 1724874262,
+            ..
+        })
+    ));
+    assert!(matches!(
+        stream_checksums(
+            r#"{"channel":"level3","data":[]}"#.as_bytes(),
+            &ChecksumOptions::default()
+        ),
+        Err(Level3Error::EmptyData)
+    ));
+}
@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use level3bug::{
+    checksum_of, compute_checksum, filter_stale, from_file, refresh_checksum, to_level2, truncate,
+    try_compute_checksum, verify_checksum, ChecksumOptions, Side,
+};
+use time::Duration;
+
+#[test]
+fn truncated_book_keeps_checksum() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let level3_data = &from_file(path).unwrap()[0];
+
+    for depth in [10, 11, 20] {
+        let mut truncated = level3_data.clone();
+        truncate(&mut truncated, depth);
+        assert_eq!(compute_checksum(&truncated), compute_checksum(level3_data));
+        assert!(to_level2(&truncated, Side::Bid).len() <= depth);
+    }
+}
+
+#[test]
+fn truncate_keeps_whole_levels() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-bug.json");
+    let level3_data = &from_file(path).unwrap()[0];
+    let opts = ChecksumOptions::level3_bug();
+
+    let mut truncated = level3_data.clone();
+    truncate(&mut truncated, opts.last_level(Side::Ask));
+    assert_eq!(
+        try_compute_checksum(&truncated, &opts).unwrap(),
+        level3_data.checksum
+    );
+    for side in [Side::Bid, Side::Ask] {
+        let levels = to_level2(&truncated, side);
+        assert_eq!(levels.len(), opts.last_level(Side::Ask));
+        assert_eq!(levels, to_level2(level3_data, side)[..levels.len()]);
+    }
+
+    truncate(&mut truncated, 0);
+    assert!(truncated.bids.is_empty() && truncated.asks.is_empty());
+}
+
+#[test]
+fn filter_stale_then_refresh_checksum() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json");
+    let mut level3_data = from_file(path).unwrap().remove(0);
+    let newest = level3_data.asks[0].timestamp;
+    for order in level3_data
+        .bids
+        .iter_mut()
+        .chain(level3_data.asks.iter_mut())
+    {
+        order.timestamp = newest;
+    }
+    level3_data.bids[1].timestamp = newest - Duration::minutes(2);
+    level3_data.asks[3].timestamp = newest - Duration::minutes(2);
+    let order_counts = (level3_data.bids.len(), level3_data.asks.len());
+    let stale_ids = [
+        level3_data.bids[1].order_id.clone(),
+        level3_data.asks[3].order_id.clone(),
+    ];
+
+    assert_eq!(filter_stale(&mut level3_data, Duration::minutes(2)), 0);
+    assert_eq!(filter_stale(&mut level3_data, Duration::minutes(1)), 2);
+    assert_eq!(
+        (level3_data.bids.len() + 1, level3_data.asks.len() + 1),
+        order_counts
+    );
+    assert!(level3_data
+        .bids
+        .iter()
+        .chain(level3_data.asks.iter())
+        .all(|order| !stale_ids.contains(&order.order_id)));
+
+    assert!(verify_checksum(&level3_data).is_err());
+    refresh_checksum(&mut level3_data).unwrap();
+    assert_eq!(
+        level3_data.checksum,
+        checksum_of(
+            &level3_data.asks,
+            &level3_data.bids,
+            &ChecksumOptions::for_symbol(&level3_data.symbol)
+        )
+    );
+    assert!(verify_checksum(&level3_data).is_ok());
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Regression vectors in `tests/vectors.ndjson`, one JSON object per line:
+//! `{"snapshot": <level3 snapshot message>, "expected_crc_input": "...", "expected_crc": 123}`.
+//! A snapshot with several symbols lists the expected values as arrays, one per symbol in the
+//! order of its data array.
+//!
+//! The checksum is computed with the precision of the snapshot's symbol, see `precision_for`. New
+//! cases for pairs with tricky precision are added as another line.
+
+use level3bug::{extract_data, try_checksum_input_string, try_compute_checksum, ChecksumOptions};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Vector {
+    snapshot: serde_json::Value,
+    expected_crc_input: PerSymbol<String>,
+    expected_crc: PerSymbol<u32>,
+}
+
+// a single value for a snapshot with one symbol
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PerSymbol<T> {
+    One(T),
+    Each(Vec<T>),
+}
+
+impl<T> PerSymbol<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            PerSymbol::One(value) => vec![value],
+            PerSymbol::Each(values) => values,
+        }
+    }
+}
+
+// line numbers and vectors, blank lines are skipped
+fn vectors() -> Vec<(usize, Vector)> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors.ndjson");
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let vector = serde_json::from_str(line)
+                .unwrap_or_else(|err| panic!("vector on line {}: {}", index + 1, err));
+            (index + 1, vector)
+        })
+        .collect()
+}
+
+#[test]
+fn reference_vectors() {
+    let vectors = vectors();
+    assert!(!vectors.is_empty());
+
+    let mut failures = Vec::new();
+    for (line, vector) in vectors {
+        let level3_data = match extract_data(vector.snapshot) {
+            Ok(level3_data) => level3_data,
+            Err(err) => {
+                failures.push(format!("line {}: {}", line, err));
+                continue;
+            }
+        };
+        let expected_crc_inputs = vector.expected_crc_input.into_vec();
+        let expected_crcs = vector.expected_crc.into_vec();
+        if expected_crc_inputs.len() != level3_data.len()
+            || expected_crcs.len() != level3_data.len()
+        {
+            failures.push(format!(
+                "line {}: {} symbols, but {} crc inputs and {} checksums",
+                line,
+                level3_data.len(),
+                expected_crc_inputs.len(),
+                expected_crcs.len()
+            ));
+            continue;
+        }
+        let expected = expected_crc_inputs.iter().zip(&expected_crcs);
+        for (data, (expected_crc_input, expected_crc)) in level3_data.iter().zip(expected) {
+            let symbol = &data.symbol;
+            let opts = ChecksumOptions::for_symbol(symbol);
+            match try_checksum_input_string(data, &opts) {
+                Ok(crc_str) if crc_str != *expected_crc_input => failures.push(format!(
+                    "line {} {}: crc input {} instead of {}",
+                    line, symbol, crc_str, expected_crc_input
+                )),
+                Ok(_) => {}
+                Err(err) => failures.push(format!("line {} {}: {}", line, symbol, err)),
+            }
+            match try_compute_checksum(data, &opts) {
+                Ok(crc) if crc != *expected_crc => failures.push(format!(
+                    "line {} {}: checksum {} instead of {}",
+                    line, symbol, crc, expected_crc
+                )),
+                Ok(_) => {}
+                Err(err) => failures.push(format!("line {} {}: {}", line, symbol, err)),
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2024 Reinhard Zitzmann (reinhard@zitzmann.io)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(feature = "ws")]
+
+use level3bug::ws::{parse_frame, WsEvent};
+use level3bug::{parse_message, Level3Error, Message, ParseOptions};
+
+fn snapshot_frame() -> String {
+    std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/level3-doc.json")).unwrap()
+}
+
+#[test]
+fn level3_frames_are_messages() {
+    let opts = ParseOptions::default();
+    let snapshot = snapshot_frame();
+    let update = snapshot.replacen(r#""type":"snapshot""#, r#""type":"update""#, 1);
+
+    let event = parse_frame(&snapshot, &opts).unwrap().unwrap();
+    assert_eq!(event, WsEvent::Message(parse_message(&snapshot).unwrap()));
+    let event = parse_frame(&update, &opts).unwrap().unwrap();
+    assert!(matches!(event, WsEvent::Message(Message::Update(data)) if data.len() == 1));
+
+    let opts = ParseOptions {
+        max_orders: Some(10),
+    };
+    assert!(matches!(
+        parse_frame(&snapshot, &opts),
+        Err(Level3Error::TooLarge { max_orders: 10 })
+    ));
+}
+
+#[test]
+fn subscribe_acks_are_events() {
+    let opts = ParseOptions::default();
+    let ack = r#"{"method":"subscribe","result":{"channel":"level3","symbol":"BTC/USD","depth":10,"snapshot":true},"success":true,"time_in":"2024-01-08T12:26:39.000000Z","time_out":"2024-01-08T12:26:39.000100Z"}"#;
+    assert_eq!(
+        parse_frame(ack, &opts).unwrap(),
+        Some(WsEvent::Subscribed {
+            symbol: "BTC/USD".to_string(),
+            depth: 10
+        })
+    );
+
+    let rejected =
+        r#"{"method":"subscribe","error":"EAccount:Invalid permissions","success":false}"#;
+    assert!(matches!(
+        parse_frame(rejected, &opts),
+        Err(Level3Error::SubscriptionRejected(error)) if error == "EAccount:Invalid permissions"
+    ));
+}
+
+#[test]
+fn heartbeats_and_status_are_skipped() {
+    let opts = ParseOptions::default();
+    for frame in [
+        r#"{"channel":"heartbeat"}"#,
+        r#"{"channel":"status","type":"update","data":[{"api_version":"v2","system":"online"}]}"#,
+        r#"{"method":"pong","time_in":"2024-01-08T12:26:39.000000Z"}"#,
+    ] {
+        assert_eq!(parse_frame(frame, &opts).unwrap(), None, "{}", frame);
+    }
+}
+
+#[test]
+fn malformed_frames_are_errors() {
+    let opts = ParseOptions::default();
+    let truncated = snapshot_frame();
+    let truncated = &truncated[..truncated.len() / 2];
+    for frame in [
+        "",
+        "not json",
+        truncated,
+        r#"{"channel":"level3","type":"bogus","data":[]}"#,
+        r#"{"channel":"level3","type":"snapshot","data":[{"symbol":"BTC/USD"}]}"#,
+    ] {
+        assert!(parse_frame(frame, &opts).is_err(), "{}", frame);
+    }
+}